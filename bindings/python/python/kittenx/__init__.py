@@ -0,0 +1,5 @@
+"""KittenTTS text-to-speech on the kittenx Rust engine."""
+
+from ._kittenx import KittenTTS, SpeechStream, __version__
+
+__all__ = ["KittenTTS", "SpeechStream", "__version__"]
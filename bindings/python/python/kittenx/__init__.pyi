@@ -0,0 +1,64 @@
+from os import PathLike
+from typing import Iterator, List, Optional, Union
+
+import numpy as np
+import numpy.typing as npt
+
+__version__: str
+
+class KittenTTS:
+    def __init__(
+        self,
+        model_dir: Optional[Union[str, PathLike[str]]] = None,
+        model: Optional[str] = None,
+        provider: str = "cpu",
+        voice: Optional[str] = None,
+        language: Optional[str] = None,
+        offline: Optional[bool] = None,
+        verbose: bool = False,
+        sessions: Optional[int] = None,
+        threads: Optional[int] = None,
+    ) -> None: ...
+    @property
+    def voices(self) -> List[str]: ...
+    @property
+    def default_voice(self) -> str: ...
+    @property
+    def sample_rate(self) -> int: ...
+    @property
+    def model_id(self) -> str: ...
+    def generate(
+        self,
+        text: str,
+        voice: Optional[str] = None,
+        speed: Optional[float] = None,
+        language: Optional[str] = None,
+        pitch: float = 0.0,
+        volume: float = 1.0,
+        sample_rate: Optional[int] = None,
+    ) -> npt.NDArray[np.float32]: ...
+    def generate_to_file(
+        self,
+        text: str,
+        path: Union[str, PathLike[str]],
+        voice: Optional[str] = None,
+        speed: Optional[float] = None,
+        language: Optional[str] = None,
+        pitch: float = 0.0,
+        volume: float = 1.0,
+        sample_rate: Optional[int] = None,
+    ) -> None: ...
+    def stream(
+        self,
+        text: str,
+        voice: Optional[str] = None,
+        speed: Optional[float] = None,
+        language: Optional[str] = None,
+        pitch: float = 0.0,
+        volume: float = 1.0,
+        sample_rate: Optional[int] = None,
+    ) -> SpeechStream: ...
+
+class SpeechStream(Iterator[npt.NDArray[np.float32]]):
+    def __iter__(self) -> SpeechStream: ...
+    def __next__(self) -> npt.NDArray[np.float32]: ...
@@ -0,0 +1,223 @@
+// Python bindings for kittenx, built with maturin (see pyproject.toml)
+// Model loading and synthesis run with the GIL released, so Python threads sharing one
+// `KittenTTS` synthesize concurrently (up to the session pool size set when loading).
+
+use kittenx::models::ModelSpec;
+use kittenx::tts::text_cleaner::split_sentences;
+use kittenx::{AccelerationProvider, KittenTTS, LogLevel, SessionOptions, SynthesisRequest};
+use numpy::PyArray1;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn to_py_err(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+/// Per-call options shared by `generate`, `generate_to_file` and `stream`
+#[derive(Clone)]
+struct Options {
+    voice: Option<String>,
+    speed: Option<f32>,
+    language: Option<String>,
+    pitch: f32,
+    volume: f32,
+    sample_rate: Option<u32>,
+}
+
+impl Options {
+    fn request(&self, text: &str) -> SynthesisRequest {
+        let mut request = SynthesisRequest::new(text).pitch(self.pitch).volume(self.volume);
+        if let Some(voice) = &self.voice {
+            request = request.voice(voice.as_str());
+        }
+        if let Some(speed) = self.speed {
+            request = request.speed(speed);
+        }
+        if let Some(language) = &self.language {
+            request = request.language(language.as_str());
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            request = request.output_sample_rate(sample_rate);
+        }
+        request
+    }
+}
+
+/// A loaded KittenTTS model
+#[pyclass(name = "KittenTTS", module = "kittenx", frozen)]
+struct PyKittenTTS {
+    tts: Arc<KittenTTS>,
+}
+
+#[pymethods]
+impl PyKittenTTS {
+    /// Load a model, downloading it first if needed. Without `model_dir` the shared
+    /// cache is used (see KITTENX_CACHE). `sessions` is the number of inference sessions
+    /// for concurrent calls from several threads, each using `threads` threads. `offline`
+    /// defaults to the KITTENX_OFFLINE environment variable.
+    #[new]
+    #[pyo3(signature = (
+        model_dir=None, model=None, provider="cpu", voice=None, language=None, offline=None, verbose=false,
+        sessions=None, threads=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python<'_>,
+        model_dir: Option<PathBuf>,
+        model: Option<String>,
+        provider: String,
+        voice: Option<String>,
+        language: Option<String>,
+        offline: Option<bool>,
+        verbose: bool,
+        sessions: Option<usize>,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        let provider: AccelerationProvider = provider.parse().map_err(to_py_err)?;
+        let spec = match model {
+            Some(id) => ModelSpec::resolve(&id).map_err(to_py_err)?,
+            None => ModelSpec::default(),
+        };
+        let mut builder = KittenTTS::builder()
+            .model(spec)
+            .provider(provider)
+            .session_options(SessionOptions { pool_size: sessions, intra_threads: threads, ..SessionOptions::default() })
+            .log_level(if verbose { LogLevel::Info } else { LogLevel::Quiet });
+        if let Some(offline) = offline {
+            builder = builder.offline(offline);
+        }
+        if let Some(model_dir) = model_dir {
+            builder = builder.model_dir(model_dir);
+        }
+        if let Some(voice) = voice {
+            builder = builder.voice(voice);
+        }
+        if let Some(language) = language {
+            builder = builder.language(language);
+        }
+        let tts = py.allow_threads(|| builder.build()).map_err(to_py_err)?;
+        Ok(Self { tts: Arc::new(tts) })
+    }
+
+    /// Names of the voices the model provides
+    #[getter]
+    fn voices(&self) -> Vec<String> {
+        self.tts.available_voices().to_vec()
+    }
+
+    #[getter]
+    fn default_voice(&self) -> &str {
+        self.tts.default_voice()
+    }
+
+    /// Sample rate of the model's output in Hz
+    #[getter]
+    fn sample_rate(&self) -> u32 {
+        self.tts.sample_rate()
+    }
+
+    #[getter]
+    fn model_id(&self) -> &str {
+        self.tts.model_id()
+    }
+
+    /// Synthesize `text` and return the samples as a float32 numpy array
+    #[pyo3(signature = (text, voice=None, speed=None, language=None, pitch=0.0, volume=1.0, sample_rate=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn generate<'py>(
+        &self,
+        py: Python<'py>,
+        text: String,
+        voice: Option<String>,
+        speed: Option<f32>,
+        language: Option<String>,
+        pitch: f32,
+        volume: f32,
+        sample_rate: Option<u32>,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let request = Options { voice, speed, language, pitch, volume, sample_rate }.request(&text);
+        let audio = py.allow_threads(|| self.tts.synthesize(&request)).map_err(to_py_err)?;
+        Ok(PyArray1::from_vec_bound(py, audio.samples))
+    }
+
+    /// Synthesize `text` into a WAV file
+    #[pyo3(signature = (text, path, voice=None, speed=None, language=None, pitch=0.0, volume=1.0, sample_rate=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn generate_to_file(
+        &self,
+        py: Python<'_>,
+        text: String,
+        path: PathBuf,
+        voice: Option<String>,
+        speed: Option<f32>,
+        language: Option<String>,
+        pitch: f32,
+        volume: f32,
+        sample_rate: Option<u32>,
+    ) -> PyResult<()> {
+        let request = Options { voice, speed, language, pitch, volume, sample_rate }.request(&text);
+        py.allow_threads(|| self.tts.synthesize(&request)?.save_wav(&path)).map_err(to_py_err)
+    }
+
+    /// Iterate over the audio of `text` one sentence at a time, so playback can start
+    /// before the whole text is synthesized
+    #[pyo3(signature = (text, voice=None, speed=None, language=None, pitch=0.0, volume=1.0, sample_rate=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn stream(
+        &self,
+        text: String,
+        voice: Option<String>,
+        speed: Option<f32>,
+        language: Option<String>,
+        pitch: f32,
+        volume: f32,
+        sample_rate: Option<u32>,
+    ) -> PySpeechStream {
+        PySpeechStream {
+            tts: Arc::clone(&self.tts),
+            options: Options { voice, speed, language, pitch, volume, sample_rate },
+            sentences: split_sentences(&text).into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KittenTTS(model_id={:?}, voices={})", self.tts.model_id(), self.tts.available_voices().len())
+    }
+}
+
+/// Iterator returned by `KittenTTS.stream`, yielding one numpy array per sentence
+#[pyclass(name = "SpeechStream", module = "kittenx")]
+struct PySpeechStream {
+    tts: Arc<KittenTTS>,
+    options: Options,
+    sentences: VecDeque<String>,
+}
+
+#[pymethods]
+impl PySpeechStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyArray1<f32>>>> {
+        let Some(sentence) = self.sentences.pop_front() else {
+            return Ok(None);
+        };
+        let request = self.options.request(&sentence);
+        let tts = &self.tts;
+        let audio = py.allow_threads(|| tts.synthesize(&request)).map_err(to_py_err)?;
+        Ok(Some(PyArray1::from_vec_bound(py, audio.samples)))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "_kittenx")]
+fn kittenx_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<PyKittenTTS>()?;
+    m.add_class::<PySpeechStream>()?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+"""Fixtures for the smoke tests: the synthetic model from tests/common/mod.rs of the
+kittenx crate, written to a model directory with one voice and a config.json."""
+
+import json
+import struct
+
+import numpy as np
+import pytest
+
+VOICE = "expr-voice-5-m"
+SAMPLE_RATE = 24000
+
+FLOAT = 1
+INT64 = 7
+
+
+def _varint(value):
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def _varint_field(number, value):
+    return _varint(number << 3) + _varint(value)
+
+
+def _bytes_field(number, data):
+    if isinstance(data, str):
+        data = data.encode()
+    return _varint(number << 3 | 2) + _varint(len(data)) + data
+
+
+def _attribute_int(name, value):
+    return _bytes_field(1, name) + _varint_field(3, value) + _varint_field(20, 2)
+
+
+def _node(op_type, inputs, output, attributes=()):
+    out = b"".join(_bytes_field(1, name) for name in inputs)
+    out += _bytes_field(2, output) + _bytes_field(3, output) + _bytes_field(4, op_type)
+    return out + b"".join(_bytes_field(5, attribute) for attribute in attributes)
+
+
+def _value_info(name, elem_type, dims):
+    """Dims are ints (fixed) or strings (symbolic); no dims leaves the shape undeclared"""
+    tensor_type = _varint_field(1, elem_type)
+    if dims:
+        shape = b"".join(
+            _bytes_field(1, _bytes_field(2, dim) if isinstance(dim, str) else _varint_field(1, dim)) for dim in dims
+        )
+        tensor_type += _bytes_field(2, shape)
+    return _bytes_field(1, name) + _bytes_field(2, _bytes_field(1, tensor_type))
+
+
+def _float_initializer(name, dims, values):
+    out = b"".join(_varint_field(1, dim) for dim in dims)
+    out += _varint_field(2, FLOAT) + _bytes_field(8, name)
+    return out + _bytes_field(9, struct.pack("<%df" % len(values), *values))
+
+
+def synthetic_model():
+    """tanh(concat(input_ids / speed * (style @ 1), style @ W)), plus all-ones durations"""
+    weights = [((i * 7 % 11) - 5.0) / 10.0 for i in range(32)]
+    nodes = [
+        _node("Cast", ["input_ids"], "ids_f", [_attribute_int("to", FLOAT)]),
+        _node("Div", ["ids_f", "speed"], "scaled"),
+        _node("MatMul", ["style", "ones"], "gain"),
+        _node("Mul", ["scaled", "gain"], "mixed"),
+        _node("MatMul", ["style", "weights"], "projected"),
+        _node("Concat", ["mixed", "projected"], "joined", [_attribute_int("axis", 1)]),
+        _node("Tanh", ["joined"], "waveform"),
+        _node("Pow", ["ids_f", "zero"], "durations"),
+    ]
+    graph = b"".join(_bytes_field(1, node) for node in nodes)
+    graph += _bytes_field(2, "parity")
+    graph += _bytes_field(5, _float_initializer("weights", [4, 8], weights))
+    graph += _bytes_field(5, _float_initializer("ones", [4, 1], [1.0] * 4))
+    graph += _bytes_field(5, _float_initializer("zero", [], [0.0]))
+    graph += _bytes_field(11, _value_info("input_ids", INT64, ["batch", "tokens"]))
+    graph += _bytes_field(11, _value_info("style", FLOAT, ["batch", 4]))
+    graph += _bytes_field(11, _value_info("speed", FLOAT, ["batch", 1]))
+    graph += _bytes_field(12, _value_info("waveform", FLOAT, []))
+    graph += _bytes_field(12, _value_info("durations", FLOAT, ["batch", "tokens"]))
+
+    opset = _varint_field(2, 13)
+    return _varint_field(1, 8) + _bytes_field(8, opset) + _bytes_field(7, graph)
+
+
+@pytest.fixture(scope="session")
+def model_dir(tmp_path_factory):
+    directory = tmp_path_factory.mktemp("model")
+    (directory / "model.onnx").write_bytes(synthetic_model())
+    rows, cols = np.indices((4, 4))
+    np.savez(directory / "voices.npz", **{VOICE: ((rows - cols) / 10.0).astype(np.float32)})
+    config = {"name": "synthetic", "model_file": "model.onnx", "voices": "voices.npz", "sample_rate": SAMPLE_RATE}
+    (directory / "config.json").write_text(json.dumps(config))
+    return directory
@@ -0,0 +1,43 @@
+"""Loads the synthetic model through the bindings and synthesizes with it."""
+
+import numpy as np
+import pytest
+
+from kittenx import KittenTTS
+
+from conftest import SAMPLE_RATE, VOICE
+
+
+@pytest.fixture(scope="module")
+def tts(model_dir):
+    return KittenTTS(model_dir=str(model_dir), offline=True)
+
+
+def test_construction(tts):
+    assert tts.voices == [VOICE]
+    assert tts.default_voice == VOICE
+    assert tts.sample_rate == SAMPLE_RATE
+
+
+def test_generate_returns_float32_samples(tts):
+    audio = tts.generate("Hello world.")
+    assert isinstance(audio, np.ndarray)
+    assert audio.dtype == np.float32
+    assert audio.ndim == 1 and audio.size > 0
+
+
+def test_generate_resamples_to_the_requested_rate(tts):
+    native = tts.generate("Hello world.")
+    resampled = tts.generate("Hello world.", sample_rate=SAMPLE_RATE // 2)
+    assert abs(resampled.size - native.size / 2) <= 1
+
+
+def test_stream_yields_one_array_per_sentence(tts):
+    chunks = list(tts.stream("First sentence. Second one!"))
+    assert len(chunks) == 2
+    assert all(isinstance(chunk, np.ndarray) and chunk.size > 0 for chunk in chunks)
+
+
+def test_unknown_voice_raises(tts):
+    with pytest.raises(RuntimeError, match="no-such-voice"):
+        tts.generate("Hello world.", voice="no-such-voice")
@@ -1,14 +1,31 @@
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 fn main() {
+    // The bundled build embeds espeak-ng-data; the archive must exist even for docs builds
+    if env::var("CARGO_FEATURE_BUNDLED_ESPEAK").is_ok() {
+        bundle_espeak_data();
+    }
+
     // Skip library detection if we're building docs
     if env::var("DOCS_RS").is_ok() {
         return;
     }
 
     println!("cargo:rerun-if-changed=build.rs");
+
+    // Only the espeak phonemizer needs the native library
+    if env::var("CARGO_FEATURE_ESPEAK").is_err() {
+        return;
+    }
+
+    // espeak-rs-sys compiles espeak-ng from its vendored sources and links it statically,
+    // so the bundled build must not pull in (or bake an rpath to) a system copy
+    if env::var("CARGO_FEATURE_BUNDLED_ESPEAK").is_ok() {
+        return;
+    }
     
     // Try to find espeak-ng library
     if let Some(lib_path) = find_espeak_library() {
@@ -29,6 +46,9 @@ fn main() {
         eprintln!("  macOS: brew install espeak-ng");
         eprintln!("  Ubuntu/Debian: sudo apt-get install espeak-ng libespeak-ng-dev");
         eprintln!("  Windows: Download from https://github.com/espeak-ng/espeak-ng/releases");
+        if env::var("CARGO_FEATURE_G2P").is_ok() {
+            eprintln!("The pure-Rust G2P backend will be used when espeak-ng fails (English only).");
+        }
     }
 }
 
@@ -109,15 +129,14 @@ fn check_library_exists(lib_dir: &str) -> bool {
 
 fn try_pkg_config() -> Option<String> {
     match Command::new("pkg-config")
-        .args(&["--libs-only-L", "espeak-ng"])
+        .args(["--libs-only-L", "espeak-ng"])
         .output()
     {
         Ok(output) if output.status.success() => {
             let output_str = String::from_utf8_lossy(&output.stdout);
             // Parse -L/path/to/lib format
             for part in output_str.split_whitespace() {
-                if part.starts_with("-L") {
-                    let path = &part[2..];
+                if let Some(path) = part.strip_prefix("-L") {
                     if check_library_exists(path) {
                         return Some(path.to_string());
                     }
@@ -133,7 +152,7 @@ fn find_with_system_commands() -> Option<String> {
     // Try different approaches based on OS
     if cfg!(target_os = "macos") {
         // Try to find via Homebrew
-        if let Ok(output) = Command::new("brew").args(&["--prefix", "espeak-ng"]).output() {
+        if let Ok(output) = Command::new("brew").args(["--prefix", "espeak-ng"]).output() {
             if output.status.success() {
                 let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
                 let lib_path = format!("{}/lib", prefix);
@@ -144,7 +163,7 @@ fn find_with_system_commands() -> Option<String> {
         }
         
         // Try locate command
-        if let Ok(output) = Command::new("locate").args(&["libespeak-ng.dylib"]).output() {
+        if let Ok(output) = Command::new("locate").args(["libespeak-ng.dylib"]).output() {
             if output.status.success() {
                 let paths = String::from_utf8_lossy(&output.stdout);
                 for line in paths.lines() {
@@ -159,7 +178,7 @@ fn find_with_system_commands() -> Option<String> {
         }
     } else if cfg!(target_os = "linux") {
         // Try ldconfig
-        if let Ok(output) = Command::new("ldconfig").args(&["-p"]).output() {
+        if let Ok(output) = Command::new("ldconfig").args(["-p"]).output() {
             if output.status.success() {
                 let output_str = String::from_utf8_lossy(&output.stdout);
                 for line in output_str.lines() {
@@ -180,4 +199,94 @@ fn find_with_system_commands() -> Option<String> {
     }
 
     None
-}
\ No newline at end of file
+}
+
+/// Pack the espeak-ng-data directory built by espeak-rs-sys into OUT_DIR so it can be
+/// embedded with `include_bytes!` and extracted at runtime on hosts without espeak-ng.
+fn bundle_espeak_data() {
+    println!("cargo:rerun-if-env-changed=ESPEAK_NG_DATA_DIR");
+    println!("cargo:rerun-if-env-changed=ESPEAK_DATA_PATH");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let archive_path = out_dir.join("espeak-ng-data.bin");
+
+    let data_dir = find_espeak_data_dir();
+    let archive = match &data_dir {
+        Some(dir) => {
+            println!("cargo:rerun-if-changed={}", dir.display());
+            let mut archive = Vec::new();
+            pack_dir(dir, dir, &mut archive).expect("Failed to pack espeak-ng-data");
+            archive
+        }
+        None => {
+            println!("cargo:warning=espeak-ng-data not found, it will not be embedded. Set ESPEAK_NG_DATA_DIR to a compiled espeak-ng-data directory.");
+            Vec::new()
+        }
+    };
+
+    fs::write(&archive_path, &archive).expect("Failed to write espeak-ng-data archive");
+    println!("cargo:rustc-env=KITTENX_ESPEAK_DATA_ARCHIVE={}", archive_path.display());
+    println!("cargo:rustc-env=KITTENX_ESPEAK_DATA_ID={:016x}", fnv1a(&archive));
+}
+
+fn find_espeak_data_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("ESPEAK_NG_DATA_DIR") {
+        let dir = PathBuf::from(dir);
+        return dir.is_dir().then_some(dir);
+    }
+
+    #[cfg(feature = "bundled-espeak")]
+    return compiled_espeak_data_dir();
+    #[cfg(not(feature = "bundled-espeak"))]
+    None
+}
+
+/// The espeak-ng-data directory compiled into espeak-rs-sys, which its build script installs
+/// into its own OUT_DIR. espeak-rs-sys declares no `links` key and so exports no DEP_*
+/// metadata; it is a build-dependency under this feature, though, so its espeak-ng is linked
+/// into this script and can report the path itself.
+#[cfg(feature = "bundled-espeak")]
+fn compiled_espeak_data_dir() -> Option<PathBuf> {
+    let mut path = std::ptr::null();
+    // A null path resolves espeak-ng's default: ESPEAK_DATA_PATH, ~/espeak-ng-data, then the
+    // directory it was built with
+    unsafe {
+        espeak_rs_sys::espeak_ng_InitializePath(std::ptr::null());
+        espeak_rs_sys::espeak_Info(&mut path);
+    }
+    if path.is_null() {
+        return None;
+    }
+    let dir = PathBuf::from(unsafe { std::ffi::CStr::from_ptr(path) }.to_str().ok()?);
+    dir.join("phontab").exists().then_some(dir)
+}
+
+/// Archive format: repeated [u32 path length][path bytes][u64 data length][data bytes]
+fn pack_dir(root: &Path, dir: &Path, archive: &mut Vec<u8>) -> std::io::Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            pack_dir(root, &path, archive)?;
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+        let data = fs::read(&path)?;
+        archive.extend_from_slice(&(relative.len() as u32).to_le_bytes());
+        archive.extend_from_slice(relative.as_bytes());
+        archive.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        archive.extend_from_slice(&data);
+    }
+    Ok(())
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
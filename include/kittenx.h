@@ -0,0 +1,93 @@
+/* kittenx C API. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef KITTENX_H
+#define KITTENX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The call succeeded
+#define KITTENX_OK 0
+
+// The call failed; `kittenx_last_error` describes why
+#define KITTENX_ERROR -1
+
+// The caller's buffer is too small; the required length was written to `out_len`
+#define KITTENX_BUFFER_TOO_SMALL -2
+
+// A streaming callback asked to stop
+#define KITTENX_CANCELLED -3
+
+// A loaded model. Safe to share between threads; free it with `kittenx_engine_free`.
+typedef struct KittenxEngine KittenxEngine;
+
+// Called with each chunk of audio (one sentence); return non-zero to stop synthesis.
+// `samples` is only valid for the duration of the call.
+typedef int32_t (*KittenxChunkCallback)(const float *samples, size_t len, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Version of the library, e.g. "0.2.0". The string is static.
+const char *kittenx_version(void);
+
+// Last error on the calling thread, or null. Valid until the next failing call on this thread.
+const char *kittenx_last_error(void);
+
+// Load the model in `model_dir`, downloading it first if it is missing (when built with
+// the `download` feature). `provider` is an acceleration provider name such as "cpu" or
+// "cuda"; null means cpu. Returns null on failure.
+//
+// # Safety
+// `model_dir` must be a valid NUL-terminated string and `provider` null or one
+KittenxEngine *kittenx_engine_new(const char *model_dir, const char *provider);
+
+// Free an engine; null is ignored
+//
+// # Safety
+// `engine` must be null or come from `kittenx_engine_new`, and not be used afterwards
+void kittenx_engine_free(KittenxEngine *engine);
+
+// Sample rate of the audio the engine produces, in Hz
+//
+// # Safety
+// `engine` must be a valid engine
+uint32_t kittenx_sample_rate(const KittenxEngine *engine);
+
+// Number of voices the model provides
+//
+// # Safety
+// `engine` must be a valid engine
+size_t kittenx_voice_count(const KittenxEngine *engine);
+
+// Name of voice `index`, or null if out of range. Owned by the engine.
+//
+// # Safety
+// `engine` must be a valid engine
+const char *kittenx_voice_name(const KittenxEngine *engine, size_t index);
+
+// Synthesize `text` into the caller's `buffer` of `capacity` samples, writing the number
+// of samples to `out_len`. If the buffer is too small, returns `KITTENX_BUFFER_TOO_SMALL`
+// with the required length in `out_len`; calling again with the same arguments and a
+// large enough buffer then copies the audio without synthesizing it again.
+// `voice` may be null for the default voice; `speed` <= 0 means 1.0.
+//
+// # Safety
+// `engine` must be a valid engine, `text` a valid NUL-terminated string, `voice` null or
+// one, `buffer` valid for `capacity` writes (or null with capacity 0), `out_len` non-null
+int32_t kittenx_synthesize(const KittenxEngine *engine, const char *text, const char *voice, float speed, float *buffer, size_t capacity, size_t *out_len);
+
+// Synthesize `text` sentence by sentence, passing each sentence's audio to `callback`
+// as soon as it is ready. Returns `KITTENX_CANCELLED` if the callback returned non-zero.
+//
+// # Safety
+// `engine` must be a valid engine, `text` a valid NUL-terminated string, `voice` null or
+// one, and `callback` must be safe to call with `user_data`
+int32_t kittenx_synthesize_stream(const KittenxEngine *engine, const char *text, const char *voice, float speed, KittenxChunkCallback callback, void *user_data);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KITTENX_H */
@@ -0,0 +1,373 @@
+// Real-time factor benchmark over a fixed corpus, used by `kittenx bench`
+// The corpus never changes between releases so numbers stay comparable across
+// providers, thread settings, model variants and machines.
+
+use crate::onnx::SessionOptions;
+use crate::tts::text_cleaner::split_sentences;
+use crate::KittenTTS;
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Standard corpus as `(category, text)`
+pub const CORPUS: &[(&str, &str)] = &[
+    ("short", "Hello world."),
+    ("short", "Turn left at the next light."),
+    ("short", "Your order has shipped."),
+    (
+        "medium",
+        "The weather today is mostly sunny, with a light breeze from the west and a high of twenty two degrees.",
+    ),
+    (
+        "medium",
+        "Please remember to save your work before closing the application. Unsaved changes will be lost.",
+    ),
+    (
+        "long",
+        "Text to speech systems turn written language into spoken audio. Modern systems first convert \
+         text into phonemes, then predict how long each sound should last, and finally generate a \
+         waveform. Small models like this one can run in real time on a laptop or a single board \
+         computer, which makes them useful for assistants, accessibility tools and devices without \
+         a network connection.",
+    ),
+    (
+        "long",
+        "It was a quiet morning in the village. The baker had already opened his shop, and the smell \
+         of fresh bread drifted down the street. Children walked to school in small groups, talking \
+         about the weekend, while an old man fed the pigeons in the square. Nobody noticed the small \
+         red balloon floating slowly above the church tower.",
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub voice: String,
+    pub speed: f32,
+    /// Untimed runs over the whole corpus before measuring
+    pub warmup: usize,
+    /// Timed runs over the whole corpus
+    pub iterations: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self { voice: "expr-voice-5-m".to_string(), speed: 1.0, warmup: 2, iterations: 5 }
+    }
+}
+
+/// Distribution of a set of timings, in milliseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyStats {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_durations(durations: &[Duration]) -> Self {
+        let mut ms: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(|a, b| a.total_cmp(b));
+        let mean_ms = if ms.is_empty() { 0.0 } else { ms.iter().sum::<f64>() / ms.len() as f64 };
+        Self {
+            mean_ms,
+            p50_ms: percentile(&ms, 50.0),
+            p90_ms: percentile(&ms, 90.0),
+            p99_ms: percentile(&ms, 99.0),
+            min_ms: ms.first().copied().unwrap_or_default(),
+            max_ms: ms.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Results for one text category (or all texts)
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryReport {
+    pub category: String,
+    pub runs: usize,
+    pub mean_chars: f64,
+    pub mean_audio_seconds: f64,
+    /// Time to synthesize the whole text in one call
+    pub latency: LatencyStats,
+    /// Time to synthesize the text's first sentence on its own (split as the streaming
+    /// APIs split it), which approximates how long a caller streaming sentence by
+    /// sentence waits before playback starts
+    pub first_sentence: LatencyStats,
+    /// Synthesis time divided by audio duration; below 1.0 is faster than real time
+    pub real_time_factor: f64,
+}
+
+/// Everything `kittenx bench` reports
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub model: String,
+    pub model_file: String,
+    pub requested_provider: String,
+    pub active_provider: String,
+    pub intra_threads: Option<usize>,
+    pub inter_threads: Option<usize>,
+    pub optimization_level: Option<String>,
+    pub sessions: usize,
+    pub voice: String,
+    pub warmup: usize,
+    pub iterations: usize,
+    /// Set by the caller, which is the one that timed model loading
+    pub load_seconds: Option<f64>,
+    pub categories: Vec<CategoryReport>,
+    pub overall: CategoryReport,
+    /// Peak resident set size of the process (Linux only)
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// One timed synthesis of a corpus text
+struct Sample {
+    category: &'static str,
+    chars: usize,
+    latency: Duration,
+    first_sentence: Duration,
+    audio_seconds: f64,
+}
+
+/// Run the corpus `options.warmup` times untimed, then `options.iterations` times timed
+pub fn run_benchmark(tts: &KittenTTS, session: &SessionOptions, options: &BenchOptions) -> Result<BenchReport> {
+    for _ in 0..options.warmup {
+        for (_, text) in CORPUS {
+            tts.generate(text, &options.voice, options.speed)?;
+        }
+    }
+
+    let mut samples = Vec::new();
+    for _ in 0..options.iterations.max(1) {
+        for &(category, text) in CORPUS {
+            let start = Instant::now();
+            let audio = tts.generate(text, &options.voice, options.speed)?;
+            let latency = start.elapsed();
+
+            let sentences = split_sentences(text);
+            let first_sentence = if sentences.len() < 2 {
+                latency
+            } else {
+                let start = Instant::now();
+                tts.generate(sentences[0], &options.voice, options.speed)?;
+                start.elapsed()
+            };
+
+            samples.push(Sample {
+                category,
+                chars: text.chars().count(),
+                latency,
+                first_sentence,
+                audio_seconds: audio.len() as f64 / f64::from(tts.sample_rate()),
+            });
+        }
+    }
+
+    let mut categories = Vec::new();
+    for (category, _) in CORPUS {
+        if categories.iter().any(|c: &CategoryReport| c.category == *category) {
+            continue;
+        }
+        let in_category: Vec<&Sample> = samples.iter().filter(|s| s.category == *category).collect();
+        categories.push(summarize(category, &in_category));
+    }
+    let overall = summarize("all", &samples.iter().collect::<Vec<_>>());
+
+    let status = tts.provider_status();
+    Ok(BenchReport {
+        model: tts.model_id().to_string(),
+        model_file: tts.model_files().model.display().to_string(),
+        requested_provider: status.requested.to_string(),
+        active_provider: status.active.to_string(),
+        intra_threads: session.intra_threads,
+        inter_threads: session.inter_threads,
+        optimization_level: session.optimization_level.map(|level| format!("{:?}", level).to_lowercase()),
+        sessions: session.pool_size.unwrap_or(1),
+        voice: options.voice.clone(),
+        warmup: options.warmup,
+        iterations: options.iterations.max(1),
+        load_seconds: None,
+        categories,
+        overall,
+        peak_memory_bytes: peak_memory_bytes(),
+    })
+}
+
+fn summarize(category: &str, samples: &[&Sample]) -> CategoryReport {
+    let runs = samples.len().max(1) as f64;
+    let latencies: Vec<Duration> = samples.iter().map(|s| s.latency).collect();
+    let first_sentence: Vec<Duration> = samples.iter().map(|s| s.first_sentence).collect();
+    let total_time: f64 = latencies.iter().map(Duration::as_secs_f64).sum();
+    let total_audio: f64 = samples.iter().map(|s| s.audio_seconds).sum();
+    CategoryReport {
+        category: category.to_string(),
+        runs: samples.len(),
+        mean_chars: samples.iter().map(|s| s.chars as f64).sum::<f64>() / runs,
+        mean_audio_seconds: total_audio / runs,
+        latency: LatencyStats::from_durations(&latencies),
+        first_sentence: LatencyStats::from_durations(&first_sentence),
+        real_time_factor: if total_audio > 0.0 { total_time / total_audio } else { 0.0 },
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Peak resident set size (`VmHWM`) of this process
+#[cfg(target_os = "linux")]
+fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_memory_bytes() -> Option<u64> {
+    None
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let threads = |t: Option<usize>| t.map(|t| t.to_string()).unwrap_or_else(|| "default".to_string());
+        writeln!(f, "Model: {} ({})", self.model, self.model_file)?;
+        writeln!(f, "Provider: {} (requested {})", self.active_provider, self.requested_provider)?;
+        writeln!(
+            f,
+            "Threads: intra {}, inter {}; optimization {}; sessions {}",
+            threads(self.intra_threads),
+            threads(self.inter_threads),
+            self.optimization_level.as_deref().unwrap_or("default"),
+            self.sessions
+        )?;
+        writeln!(f, "Voice: {}; warmup {}, iterations {}", self.voice, self.warmup, self.iterations)?;
+        if let Some(load) = self.load_seconds {
+            writeln!(f, "Model load: {:.2}s", load)?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<8} {:>5} {:>7} {:>9} {:>9} {:>9} {:>9} {:>13} {:>7}",
+            "texts", "runs", "audio", "mean", "p50", "p90", "p99", "1st sent p50", "RTF"
+        )?;
+        for report in self.categories.iter().chain(std::iter::once(&self.overall)) {
+            writeln!(
+                f,
+                "{:<8} {:>5} {:>6.2}s {:>7.1}ms {:>7.1}ms {:>7.1}ms {:>7.1}ms {:>11.1}ms {:>7.3}",
+                report.category,
+                report.runs,
+                report.mean_audio_seconds,
+                report.latency.mean_ms,
+                report.latency.p50_ms,
+                report.latency.p90_ms,
+                report.latency.p99_ms,
+                report.first_sentence.p50_ms,
+                report.real_time_factor
+            )?;
+        }
+        match self.peak_memory_bytes {
+            Some(bytes) => write!(f, "\nPeak memory: {:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+            None => write!(f, "\nPeak memory: not available on this platform"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    fn sample(chars: usize, latency_ms: u64, first_sentence_ms: u64, audio_seconds: f64) -> Sample {
+        Sample {
+            category: "short",
+            chars,
+            latency: Duration::from_millis(latency_ms),
+            first_sentence: Duration::from_millis(first_sentence_ms),
+            audio_seconds,
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sorted: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 5.0);
+        assert_eq!(percentile(&sorted, 90.0), 9.0);
+        assert_eq!(percentile(&sorted, 99.0), 10.0);
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn latency_stats_sort_their_input() {
+        let stats = LatencyStats::from_durations(&ms(&[30, 10, 20, 40]));
+        assert_eq!(stats.mean_ms, 25.0);
+        assert_eq!(stats.p50_ms, 20.0);
+        assert_eq!(stats.p90_ms, 40.0);
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.max_ms, 40.0);
+
+        let empty = LatencyStats::from_durations(&[]);
+        assert_eq!((empty.mean_ms, empty.p50_ms, empty.max_ms), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn summary_divides_synthesis_time_by_audio_time() {
+        let samples = [sample(10, 100, 100, 1.0), sample(20, 300, 50, 3.0)];
+        let report = summarize("short", &samples.iter().collect::<Vec<_>>());
+        assert_eq!(report.runs, 2);
+        assert_eq!(report.mean_chars, 15.0);
+        assert_eq!(report.mean_audio_seconds, 2.0);
+        assert!((report.real_time_factor - 0.1).abs() < 1e-12, "{}", report.real_time_factor);
+        assert_eq!(report.latency.max_ms, 300.0);
+        assert_eq!(report.first_sentence.min_ms, 50.0);
+
+        let silent = summarize("none", &[]);
+        assert_eq!((silent.runs, silent.real_time_factor), (0, 0.0));
+    }
+
+    #[test]
+    fn report_lists_every_category_and_the_total() {
+        let category = summarize("short", &[&sample(12, 40, 40, 0.8)]);
+        let report = BenchReport {
+            model: "nano-0.8".to_string(),
+            model_file: "model.onnx".to_string(),
+            requested_provider: "cuda".to_string(),
+            active_provider: "cpu".to_string(),
+            intra_threads: Some(4),
+            inter_threads: None,
+            optimization_level: None,
+            sessions: 1,
+            voice: "expr-voice-5-m".to_string(),
+            warmup: 0,
+            iterations: 1,
+            load_seconds: Some(1.5),
+            categories: vec![category.clone()],
+            overall: CategoryReport { category: "all".to_string(), ..category },
+            peak_memory_bytes: None,
+        };
+        let text = report.to_string();
+        assert!(text.contains("Provider: cpu (requested cuda)"), "{}", text);
+        assert!(text.contains("Threads: intra 4, inter default; optimization default; sessions 1"), "{}", text);
+        assert!(text.contains("Model load: 1.50s"), "{}", text);
+        let rows: Vec<&str> = text.lines().filter(|line| line.contains("ms")).collect();
+        assert_eq!(rows.len(), 2, "{}", text);
+        assert!(rows[0].starts_with("short") && rows[1].starts_with("all"), "{}", text);
+        assert!(rows[1].trim_end().ends_with("0.050"), "{}", text);
+        assert!(text.ends_with("Peak memory: not available on this platform"), "{}", text);
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["overall"]["first_sentence"]["p50_ms"], 40.0);
+    }
+}
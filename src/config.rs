@@ -0,0 +1,302 @@
+// CLI defaults from config files and KITTENX_* environment variables
+// Precedence, highest first: command-line flags, environment variables, ./kittenx.toml,
+// the user config file, then the defaults built into the CLI.
+
+use anyhow::{Context, Result};
+use clap::builder::Str;
+use clap::Command;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the user config file to read instead of the default one
+pub const CONFIG_ENV: &str = "KITTENX_CONFIG";
+
+/// Project-local config file, read from the current directory
+pub const PROJECT_FILE: &str = "kittenx.toml";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Value,
+    /// Relative paths in a config file are resolved against the file's directory
+    Path,
+    /// A boolean flag, with the id of the paired flag that turns it back off
+    /// (`--fp16` and `--no-fp16`), so a default from the config can be overridden
+    Flag(&'static str),
+    /// A boolean setting whose command-line flag turns it off, with the id of the paired
+    /// flag that turns it back on (`trim` set through `--no-trim` and `--trim`)
+    NegatedFlag(&'static str),
+}
+
+struct Setting {
+    /// Key in the config file; dotted keys live in a table (`session.threads`)
+    key: &'static str,
+    env: &'static str,
+    /// Id of the command-line argument it sets the default of. Settings without one are
+    /// passed on through their environment variable.
+    arg: Option<&'static str>,
+    kind: Kind,
+}
+
+const fn setting(key: &'static str, env: &'static str, arg: &'static str, kind: Kind) -> Setting {
+    Setting { key, env, arg: Some(arg), kind }
+}
+
+const SETTINGS: &[Setting] = &[
+    setting("voice", "KITTENX_VOICE", "voice", Kind::Value),
+    setting("speed", "KITTENX_SPEED", "speed", Kind::Value),
+    setting("language", "KITTENX_LANGUAGE", "language", Kind::Value),
+    setting("model", "KITTENX_MODEL", "model", Kind::Value),
+    setting("model_dir", "KITTENX_MODEL_DIR", "model_dir", Kind::Path),
+    setting("provider", "KITTENX_PROVIDER", "provider", Kind::Value),
+    setting("phonemizer", "KITTENX_PHONEMIZER", "phonemizer", Kind::Value),
+    // Read by the G2P phonemizer (see `kittenx::tts::g2p::CMUDICT_ENV`)
+    Setting { key: "lexicon", env: "KITTENX_CMUDICT", arg: None, kind: Kind::Path },
+    setting("input_format", "KITTENX_INPUT_FORMAT", "input_format", Kind::Value),
+    setting("code_blocks", "KITTENX_CODE_BLOCKS", "code_blocks", Kind::Value),
+    setting("audio.format", "KITTENX_FORMAT", "format", Kind::Value),
+    setting("audio.trim", "KITTENX_TRIM", "no_trim", Kind::NegatedFlag("trim")),
+    setting("audio.normalize_peak", "KITTENX_NORMALIZE_PEAK", "normalize_peak", Kind::Value),
+    setting("session.backend", "KITTENX_BACKEND", "backend", Kind::Value),
+    setting("session.threads", "KITTENX_THREADS", "threads", Kind::Value),
+    setting("session.inter_threads", "KITTENX_INTER_THREADS", "inter_threads", Kind::Value),
+    setting("session.opt_level", "KITTENX_OPT_LEVEL", "opt_level", Kind::Value),
+    setting("session.parallel_execution", "KITTENX_PARALLEL_EXECUTION", "parallel_execution", Kind::Flag("no_parallel_execution")),
+    setting("session.deterministic", "KITTENX_DETERMINISTIC", "deterministic", Kind::Flag("no_deterministic")),
+    setting("session.sessions", "KITTENX_SESSIONS", "sessions", Kind::Value),
+    setting("session.device_id", "KITTENX_DEVICE_ID", "device_id", Kind::Value),
+    setting("session.fp16", "KITTENX_FP16", "fp16", Kind::Flag("no_fp16")),
+    setting("session.trt_engine_cache", "KITTENX_TRT_ENGINE_CACHE", "trt_engine_cache", Kind::Path),
+    setting("session.openvino_device", "KITTENX_OPENVINO_DEVICE", "openvino_device", Kind::Value),
+];
+
+/// Where a setting's value came from
+#[derive(Debug, Clone)]
+pub enum Source {
+    Env(&'static str),
+    File(PathBuf),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Settings read from the config files and environment, applied as argument defaults
+#[derive(Default)]
+pub struct Defaults {
+    values: Vec<(&'static Setting, String, Source)>,
+    /// Config files considered, lowest precedence first, and whether they exist
+    files: Vec<(PathBuf, bool)>,
+}
+
+impl Defaults {
+    pub fn load() -> Result<Self> {
+        let mut defaults = Self::default();
+        let explicit = std::env::var_os(CONFIG_ENV).map(PathBuf::from);
+        let user_file = explicit.clone().or_else(|| user_config_dir().map(|dir| dir.join("kittenx").join("config.toml")));
+
+        // Lowest precedence first, so later sources replace earlier values
+        for path in user_file.into_iter().chain([PathBuf::from(PROJECT_FILE)]) {
+            let exists = path.is_file();
+            if exists {
+                defaults.read_file(&path)?;
+            } else if explicit.as_ref() == Some(&path) {
+                anyhow::bail!("Config file {} (from {}) does not exist", path.display(), CONFIG_ENV);
+            }
+            defaults.files.push((path, exists));
+        }
+
+        for setting in SETTINGS {
+            if let Some(value) = std::env::var(setting.env).ok().filter(|v| !v.is_empty()) {
+                let value = normalize(setting, value).with_context(|| format!("Invalid {}", setting.env))?;
+                defaults.set(setting, value, Source::Env(setting.env));
+            }
+        }
+        Ok(defaults)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        let mut entries = Vec::new();
+        for (key, value) in &table {
+            match value {
+                toml::Value::Table(inner) => {
+                    entries.extend(inner.iter().map(|(name, value)| (format!("{}.{}", key, name), value)))
+                }
+                value => entries.push((key.clone(), value)),
+            }
+        }
+        for (key, value) in entries {
+            let setting = SETTINGS
+                .iter()
+                .find(|s| s.key == key)
+                .ok_or_else(|| anyhow::anyhow!("Unknown setting `{}` in {}", key, path.display()))?;
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                other => anyhow::bail!(
+                    "`{}` in {} must be a string, number or boolean, not {}",
+                    key,
+                    path.display(),
+                    other.type_str()
+                ),
+            };
+            let mut value = normalize(setting, value).with_context(|| format!("Invalid `{}` in {}", key, path.display()))?;
+            if setting.kind == Kind::Path && Path::new(&value).is_relative() {
+                value = base.join(&value).display().to_string();
+            }
+            self.set(setting, value, Source::File(path.to_path_buf()));
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, setting: &'static Setting, value: String, source: Source) {
+        self.values.retain(|(s, _, _)| s.key != setting.key);
+        self.values.push((setting, value, source));
+    }
+
+    /// Export settings that are read from the environment rather than passed as arguments.
+    /// Call this before starting any threads: changing the environment isn't thread-safe.
+    pub fn export_env(&self) {
+        for (setting, value, source) in &self.values {
+            if setting.arg.is_none() && matches!(source, Source::File(_)) {
+                std::env::set_var(setting.env, value);
+            }
+        }
+    }
+
+    /// Use the settings as defaults for the matching arguments of every subcommand
+    pub fn apply(&self, mut command: Command) -> Command {
+        for (setting, value, _) in &self.values {
+            let Some(id) = setting.arg else { continue };
+            if !command.get_arguments().any(|arg| arg.get_id() == id) {
+                continue;
+            }
+            let opposite = match setting.kind {
+                Kind::Flag(opposite) | Kind::NegatedFlag(opposite) => Some(opposite),
+                _ => None,
+            };
+            let value = match setting.kind {
+                Kind::NegatedFlag(_) => if value == "true" { "false" } else { "true" }.to_string(),
+                _ => value.clone(),
+            };
+            command = command.mut_arg(id, |arg| {
+                let arg = arg.default_value(Str::from(value));
+                match opposite {
+                    // The opposite flag defaults to "false", so this only applies when it is given
+                    Some(opposite) => arg.default_value_if(opposite, "true", Some("false")),
+                    None => arg,
+                }
+            });
+        }
+        let names: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+        for name in names {
+            command = command.mut_subcommand(name, |sub| self.apply(sub));
+        }
+        command
+    }
+
+    /// Print the config files and every setting with its effective value and source.
+    /// `command` is the unmodified CLI, used to look up the built-in defaults.
+    pub fn show(&self, command: &Command) {
+        println!("Config files (lowest precedence first):");
+        for (path, exists) in &self.files {
+            println!("  {}{}", path.display(), if *exists { "" } else { " (not found)" });
+        }
+        println!();
+        for setting in SETTINGS {
+            match self.values.iter().find(|(s, _, _)| s.key == setting.key) {
+                Some((_, value, source)) => println!("{:<28} {:<24} ({})", setting.key, value, source),
+                None => {
+                    let value = builtin_default(command, setting).unwrap_or_else(|| "-".to_string());
+                    println!("{:<28} {:<24} (default, {})", setting.key, value, setting.env);
+                }
+            }
+        }
+    }
+}
+
+/// Check a setting's value, spelling booleans as `true` or `false` for clap
+fn normalize(setting: &Setting, value: String) -> Result<String> {
+    if !matches!(setting.kind, Kind::Flag(_) | Kind::NegatedFlag(_)) {
+        return Ok(value);
+    }
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok("true".to_string()),
+        "0" | "false" | "no" | "off" => Ok("false".to_string()),
+        _ => anyhow::bail!("expected true or false, got '{}'", value),
+    }
+}
+
+/// The CLI's own default for a setting, from the first subcommand that has its argument
+fn builtin_default(command: &Command, setting: &Setting) -> Option<String> {
+    let id = setting.arg?;
+    if let Some(arg) = command.get_arguments().find(|arg| arg.get_id() == id) {
+        let value = arg.get_default_values().first()?.to_string_lossy().into_owned();
+        return Some(match setting.kind {
+            Kind::NegatedFlag(_) => (value != "true").to_string(),
+            _ => value,
+        });
+    }
+    command.get_subcommands().find_map(|sub| builtin_default(sub, setting))
+}
+
+/// `$XDG_CONFIG_HOME`, else `~/.config` (on every platform, like most CLI tools)
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    fn setting(key: &str) -> &'static Setting {
+        SETTINGS.iter().find(|s| s.key == key).unwrap()
+    }
+
+    fn command(defaults: &Defaults) -> Command {
+        let flag = |id: &'static str, opposite: &'static str| {
+            let long = id.replace('_', "-");
+            Arg::new(id).long(long).action(ArgAction::SetTrue).overrides_with(opposite)
+        };
+        defaults.apply(
+            Command::new("kittenx")
+                .arg(flag("fp16", "no_fp16"))
+                .arg(flag("no_fp16", "fp16"))
+                .arg(flag("no_trim", "trim"))
+                .arg(flag("trim", "no_trim")),
+        )
+    }
+
+    fn parse(defaults: &Defaults, args: &[&str]) -> (bool, bool) {
+        let matches = command(defaults).get_matches_from(std::iter::once("kittenx").chain(args.iter().copied()));
+        (matches.get_flag("fp16"), matches.get_flag("no_trim"))
+    }
+
+    #[test]
+    fn command_line_flags_override_boolean_settings() {
+        let mut defaults = Defaults::default();
+        defaults.set(setting("session.fp16"), "true".to_string(), Source::Env("KITTENX_FP16"));
+        defaults.set(setting("audio.trim"), "false".to_string(), Source::Env("KITTENX_TRIM"));
+
+        assert_eq!(parse(&defaults, &[]), (true, true));
+        assert_eq!(parse(&defaults, &["--no-fp16", "--trim"]), (false, false));
+        assert_eq!(parse(&defaults, &["--no-fp16", "--fp16"]), (true, true));
+        assert_eq!(parse(&Defaults::default(), &["--fp16", "--no-trim"]), (true, true));
+    }
+}
@@ -0,0 +1,265 @@
+// C ABI for embedding kittenx in non-Rust applications
+// The header is generated from this file with cbindgen (see cbindgen.toml) and checked in
+// as include/kittenx.h. Every function catches panics and reports failures through a
+// status code plus `kittenx_last_error`, which is per thread.
+
+use crate::onnx::AccelerationProvider;
+use crate::tts::text_cleaner::split_sentences;
+use crate::tts::{KittenTTS, LogLevel, SynthesisRequest};
+use anyhow::{anyhow, Context, Result};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Mutex;
+
+/// The call succeeded
+pub const KITTENX_OK: i32 = 0;
+/// The call failed; `kittenx_last_error` describes why
+pub const KITTENX_ERROR: i32 = -1;
+/// The caller's buffer is too small; the required length was written to `out_len`
+pub const KITTENX_BUFFER_TOO_SMALL: i32 = -2;
+/// A streaming callback asked to stop
+pub const KITTENX_CANCELLED: i32 = -3;
+
+/// Called with each chunk of audio (one sentence); return non-zero to stop synthesis.
+/// `samples` is only valid for the duration of the call.
+pub type KittenxChunkCallback =
+    Option<unsafe extern "C" fn(samples: *const f32, len: usize, user_data: *mut c_void) -> i32>;
+
+/// A loaded model. Safe to share between threads; free it with `kittenx_engine_free`.
+pub struct KittenxEngine {
+    tts: KittenTTS,
+    voice_names: Vec<CString>,
+    /// Audio that did not fit the caller's buffer, kept so the retry doesn't synthesize again
+    pending: Mutex<Option<(PendingKey, Vec<f32>)>>,
+}
+
+#[derive(PartialEq)]
+struct PendingKey {
+    text: String,
+    voice: String,
+    speed: u32,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning errors and panics into `KITTENX_ERROR` and the thread's last error
+fn guard(f: impl FnOnce() -> Result<i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(status)) => status,
+        Ok(Err(e)) => {
+            set_last_error(format!("{:#}", e));
+            KITTENX_ERROR
+        }
+        Err(_) => {
+            set_last_error("kittenx panicked".to_string());
+            KITTENX_ERROR
+        }
+    }
+}
+
+/// # Safety
+/// `s` must be null or a valid NUL-terminated string
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(anyhow!("{} must not be null", name));
+    }
+    CStr::from_ptr(s).to_str().with_context(|| format!("{} is not valid UTF-8", name))
+}
+
+/// # Safety
+/// `s` must be null or a valid NUL-terminated string
+unsafe fn opt_str_arg<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if s.is_null() {
+        Ok(None)
+    } else {
+        str_arg(s, name).map(Some)
+    }
+}
+
+fn request(engine: &KittenxEngine, text: &str, voice: Option<&str>, speed: f32) -> SynthesisRequest {
+    let voice = voice.unwrap_or_else(|| engine.tts.default_voice());
+    SynthesisRequest::new(text).voice(voice).speed(if speed > 0.0 { speed } else { 1.0 })
+}
+
+/// Version of the library, e.g. "0.2.0". The string is static.
+#[no_mangle]
+pub extern "C" fn kittenx_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Last error on the calling thread, or null. Valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn kittenx_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Load the model in `model_dir`, downloading it first if it is missing (when built with
+/// the `download` feature). `provider` is an acceleration provider name such as "cpu" or
+/// "cuda"; null means cpu. Returns null on failure.
+///
+/// # Safety
+/// `model_dir` must be a valid NUL-terminated string and `provider` null or one
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_engine_new(model_dir: *const c_char, provider: *const c_char) -> *mut KittenxEngine {
+    let mut engine = ptr::null_mut();
+    guard(|| {
+        let model_dir = str_arg(model_dir, "model_dir")?;
+        let provider = match opt_str_arg(provider, "provider")? {
+            Some(name) => name.parse()?,
+            None => AccelerationProvider::Cpu,
+        };
+        let tts = KittenTTS::builder()
+            .model_dir(model_dir)
+            .provider(provider)
+            .log_level(LogLevel::Quiet)
+            .build()?;
+        let voice_names = tts
+            .available_voices()
+            .iter()
+            .map(|voice| CString::new(voice.as_str()).context("Voice name contains NUL"))
+            .collect::<Result<_>>()?;
+        engine = Box::into_raw(Box::new(KittenxEngine { tts, voice_names, pending: Mutex::new(None) }));
+        Ok(KITTENX_OK)
+    });
+    engine
+}
+
+/// Free an engine; null is ignored
+///
+/// # Safety
+/// `engine` must be null or come from `kittenx_engine_new`, and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_engine_free(engine: *mut KittenxEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Sample rate of the audio the engine produces, in Hz
+///
+/// # Safety
+/// `engine` must be a valid engine
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_sample_rate(engine: *const KittenxEngine) -> u32 {
+    engine.as_ref().map_or(0, |engine| engine.tts.sample_rate())
+}
+
+/// Number of voices the model provides
+///
+/// # Safety
+/// `engine` must be a valid engine
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_voice_count(engine: *const KittenxEngine) -> usize {
+    engine.as_ref().map_or(0, |engine| engine.voice_names.len())
+}
+
+/// Name of voice `index`, or null if out of range. Owned by the engine.
+///
+/// # Safety
+/// `engine` must be a valid engine
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_voice_name(engine: *const KittenxEngine, index: usize) -> *const c_char {
+    engine
+        .as_ref()
+        .and_then(|engine| engine.voice_names.get(index))
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// Synthesize `text` into the caller's `buffer` of `capacity` samples, writing the number
+/// of samples to `out_len`. If the buffer is too small, returns `KITTENX_BUFFER_TOO_SMALL`
+/// with the required length in `out_len`; calling again with the same arguments and a
+/// large enough buffer then copies the audio without synthesizing it again.
+/// `voice` may be null for the default voice; `speed` <= 0 means 1.0.
+///
+/// # Safety
+/// `engine` must be a valid engine, `text` a valid NUL-terminated string, `voice` null or
+/// one, `buffer` valid for `capacity` writes (or null with capacity 0), `out_len` non-null
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_synthesize(
+    engine: *const KittenxEngine,
+    text: *const c_char,
+    voice: *const c_char,
+    speed: f32,
+    buffer: *mut f32,
+    capacity: usize,
+    out_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        let engine = engine.as_ref().context("engine must not be null")?;
+        let out_len = out_len.as_mut().context("out_len must not be null")?;
+        let text = str_arg(text, "text")?;
+        let voice = opt_str_arg(voice, "voice")?;
+        let key = PendingKey {
+            text: text.to_string(),
+            voice: voice.unwrap_or_default().to_string(),
+            speed: speed.to_bits(),
+        };
+
+        // Hold the lock only around `pending` itself, so callers sharing the engine
+        // synthesize concurrently on the session pool
+        let lock_pending = || engine.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = {
+            let mut pending = lock_pending();
+            match pending.take() {
+                Some((pending_key, samples)) if pending_key == key => Some(samples),
+                other => {
+                    *pending = other;
+                    None
+                }
+            }
+        };
+        let samples = match cached {
+            Some(samples) => samples,
+            None => engine.tts.synthesize(&request(engine, text, voice, speed))?.samples,
+        };
+
+        *out_len = samples.len();
+        if samples.len() > capacity || (buffer.is_null() && !samples.is_empty()) {
+            *lock_pending() = Some((key, samples));
+            return Ok(KITTENX_BUFFER_TOO_SMALL);
+        }
+        if !samples.is_empty() {
+            ptr::copy_nonoverlapping(samples.as_ptr(), buffer, samples.len());
+        }
+        Ok(KITTENX_OK)
+    })
+}
+
+/// Synthesize `text` sentence by sentence, passing each sentence's audio to `callback`
+/// as soon as it is ready. Returns `KITTENX_CANCELLED` if the callback returned non-zero.
+///
+/// # Safety
+/// `engine` must be a valid engine, `text` a valid NUL-terminated string, `voice` null or
+/// one, and `callback` must be safe to call with `user_data`
+#[no_mangle]
+pub unsafe extern "C" fn kittenx_synthesize_stream(
+    engine: *const KittenxEngine,
+    text: *const c_char,
+    voice: *const c_char,
+    speed: f32,
+    callback: KittenxChunkCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    guard(|| {
+        let engine = engine.as_ref().context("engine must not be null")?;
+        let callback = callback.context("callback must not be null")?;
+        let text = str_arg(text, "text")?;
+        let voice = opt_str_arg(voice, "voice")?;
+        for sentence in split_sentences(text) {
+            let audio = engine.tts.synthesize(&request(engine, sentence, voice, speed))?;
+            if callback(audio.samples.as_ptr(), audio.samples.len(), user_data) != 0 {
+                return Ok(KITTENX_CANCELLED);
+            }
+        }
+        Ok(KITTENX_OK)
+    })
+}
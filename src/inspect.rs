@@ -0,0 +1,119 @@
+// `kittenx phonemize` and `kittenx tokenize`: show the text pipeline stages without
+// loading the model, for debugging mispronunciations
+
+use anyhow::Result;
+use kittenx::tts::phonemizer::{self, phonemize_with, text_to_phonemes_simple, PhonemizerBackend};
+use kittenx::tts::text_cleaner::{split_sentences, TextCleaner};
+use serde::Serialize;
+use std::io::Read;
+
+/// `text`, or stdin if it is not given
+pub fn read_input(text: Option<String>) -> Result<String> {
+    match text {
+        Some(text) => Ok(text),
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SentencePhonemes {
+    pub text: String,
+    /// The sentence with runs of whitespace collapsed, as passed to the phonemizer
+    pub normalized: String,
+    pub language: String,
+    /// Phonemes as the model gets them, before tokenization
+    pub ipa: String,
+}
+
+/// Phonemize `text` one sentence at a time. `auto` detects the language of each sentence.
+pub fn phonemize(text: &str, language: &str, backend: Option<PhonemizerBackend>) -> Result<Vec<SentencePhonemes>> {
+    let phonemizer = backend.map(PhonemizerBackend::build).transpose()?;
+    split_sentences(text)
+        .into_iter()
+        .map(|sentence| {
+            let normalized = sentence.split_whitespace().collect::<Vec<_>>().join(" ");
+            let language = if language.eq_ignore_ascii_case("auto") {
+                phonemizer::detect_language(&normalized).unwrap_or_else(|| "en-us".to_string())
+            } else {
+                language.to_string()
+            };
+            let ipa = match &phonemizer {
+                Some(phonemizer) => phonemize_with(phonemizer.as_ref(), &normalized, &language)?,
+                None => text_to_phonemes_simple(&normalized, &language)?,
+            };
+            Ok(SentencePhonemes { text: sentence.to_string(), normalized, language, ipa })
+        })
+        .collect()
+}
+
+pub fn print_phonemes(sentences: &[SentencePhonemes]) {
+    for (i, sentence) in sentences.iter().enumerate() {
+        println!("Sentence {} ({})", i + 1, sentence.language);
+        if sentence.normalized != sentence.text {
+            println!("  text:       {}", sentence.text);
+        }
+        println!("  normalized: {}", sentence.normalized);
+        println!("  ipa:        {}", sentence.ipa);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Token {
+    pub id: i64,
+    pub symbol: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DroppedChar {
+    /// Char offset in the input
+    pub position: usize,
+    pub char: String,
+    /// Unicode code point, e.g. `U+0303`, since dropped characters are often combining marks
+    pub codepoint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tokenization {
+    pub ipa: String,
+    /// Token ids as fed to the model, including the pad token at both ends
+    pub tokens: Vec<Token>,
+    pub dropped: Vec<DroppedChar>,
+}
+
+pub fn tokenize(ipa: &str) -> Tokenization {
+    let cleaner = TextCleaner::new();
+    let mut ids = cleaner.clean(ipa);
+    ids.insert(0, 0);
+    ids.push(0);
+
+    let tokens = ids
+        .into_iter()
+        .map(|id| Token { id, symbol: cleaner.symbol(id).map(String::from).unwrap_or_default() })
+        .collect();
+    let dropped = cleaner
+        .dropped_chars(ipa)
+        .into_iter()
+        .map(|(position, c)| DroppedChar { position, char: c.to_string(), codepoint: format!("U+{:04X}", c as u32) })
+        .collect();
+    Tokenization { ipa: ipa.to_string(), tokens, dropped }
+}
+
+pub fn print_tokens(tokenization: &Tokenization) {
+    println!("IPA: {}", tokenization.ipa);
+    println!("Tokens ({}, with the pad token 0 at both ends):", tokenization.tokens.len());
+    for token in &tokenization.tokens {
+        println!("  {:>4}  {:?}", token.id, token.symbol);
+    }
+    if tokenization.dropped.is_empty() {
+        println!("Dropped characters: none");
+    } else {
+        println!("Dropped characters:");
+        for dropped in &tokenization.dropped {
+            println!("  position {:>3}: {:?} ({})", dropped.position, dropped.char, dropped.codepoint);
+        }
+    }
+}
@@ -1,9 +1,18 @@
+pub mod bench;
+pub mod ffi;
+pub mod models;
 pub mod tts;
 pub mod onnx;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use tts::KittenTTS;
-pub use onnx::AccelerationProvider;
+pub use tts::{KittenTTS, KittenTTSBuilder, LogLevel, PostProcessing};
+pub use tts::{SynthesisRequest, SynthesizedAudio};
+pub use tts::markup::{CodeBlocks, InputFormat};
+pub use tts::phonemizer::{Phonemizer, PhonemizerBackend};
+pub use models::{ModelFiles, ModelLocation, ModelSpec};
+pub use onnx::{AccelerationProvider, ModelOutputs, ModelSignature, OptimizationLevel, ProviderOptions, ProviderStatus, SessionOptions};
 
 use anyhow::Result;
 use std::path::Path;
@@ -13,11 +22,13 @@ pub struct KittenXLib {
 }
 
 impl KittenXLib {
+    #[cfg(feature = "download")]
     pub async fn new<P: AsRef<Path>>(model_dir: P) -> Result<Self> {
         let tts = KittenTTS::new(model_dir.as_ref()).await?;
         Ok(Self { tts })
     }
     
+    #[cfg(feature = "download")]
     pub async fn with_provider<P: AsRef<Path>>(
         model_dir: P, 
         provider: AccelerationProvider
@@ -25,11 +36,97 @@ impl KittenXLib {
         let tts = KittenTTS::with_provider(model_dir.as_ref(), provider).await?;
         Ok(Self { tts })
     }
+
+    #[cfg(feature = "download")]
+    pub async fn with_options<P: AsRef<Path>>(
+        model_dir: P,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::with_options(model_dir.as_ref(), provider, options).await?;
+        Ok(Self { tts })
+    }
+
+    #[cfg(feature = "download")]
+    pub async fn with_model<P: AsRef<Path>>(
+        model_dir: P,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::with_model(model_dir.as_ref(), model, provider, options).await?;
+        Ok(Self { tts })
+    }
+
+    /// Load a model from the shared cache, fetching it if needed
+    #[cfg(feature = "download")]
+    pub async fn from_cache(
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::from_cache(model, provider, options).await?;
+        Ok(Self { tts })
+    }
+
+    /// Load without an async runtime (see `KittenTTS::with_model_blocking`)
+    pub fn new_blocking<P: AsRef<Path>>(model_dir: P) -> Result<Self> {
+        let tts = KittenTTS::new_blocking(model_dir.as_ref())?;
+        Ok(Self { tts })
+    }
+
+    pub fn with_provider_blocking<P: AsRef<Path>>(model_dir: P, provider: AccelerationProvider) -> Result<Self> {
+        let tts = KittenTTS::with_provider_blocking(model_dir.as_ref(), provider)?;
+        Ok(Self { tts })
+    }
+
+    pub fn with_options_blocking<P: AsRef<Path>>(
+        model_dir: P,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::with_options_blocking(model_dir.as_ref(), provider, options)?;
+        Ok(Self { tts })
+    }
+
+    pub fn with_model_blocking<P: AsRef<Path>>(
+        model_dir: P,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::with_model_blocking(model_dir.as_ref(), model, provider, options)?;
+        Ok(Self { tts })
+    }
+
+    /// Load from explicit file paths without any network access
+    pub fn from_files(
+        files: &ModelFiles,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let tts = KittenTTS::from_files(files, provider, options)?;
+        Ok(Self { tts })
+    }
+
+    #[cfg(feature = "download")]
+    pub async fn with_phonemizer<P: AsRef<Path>>(
+        model_dir: P,
+        provider: AccelerationProvider,
+        phonemizer: Box<dyn Phonemizer>,
+    ) -> Result<Self> {
+        let tts = KittenTTS::with_phonemizer(model_dir.as_ref(), provider, phonemizer).await?;
+        Ok(Self { tts })
+    }
     
     pub fn generate_speech(&self, text: &str, voice: &str, speed: f32) -> Result<Vec<f32>> {
         self.tts.generate(text, voice, speed)
     }
     
+    pub fn generate_batch(&self, requests: &[(&str, &str, f32)]) -> Result<Vec<Vec<f32>>> {
+        self.tts.generate_batch(requests)
+    }
+
     pub fn generate_to_file<P: AsRef<Path>>(
         &self, 
         text: &str, 
@@ -40,6 +137,10 @@ impl KittenXLib {
         self.tts.generate_to_file(text, voice, speed, output_path.as_ref())
     }
     
+    pub fn provider_status(&self) -> &ProviderStatus {
+        self.tts.provider_status()
+    }
+
     pub fn available_voices(&self) -> Vec<String> {
         self.tts.available_voices().to_vec()
     }
@@ -1,8 +1,21 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use kittenx::KittenTTS;
-use kittenx::onnx::AccelerationProvider;
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use kittenx::{CodeBlocks, InputFormat, KittenTTS, LogLevel, PostProcessing, SynthesisRequest};
+use kittenx::bench::{run_benchmark, BenchOptions, CORPUS};
+use kittenx::models::{verify_model, ModelLocation, ModelSpec, DEFAULT_MODEL_ID, REGISTRY};
+use kittenx::onnx::proto::{elem_type_name, ModelSummary};
+use kittenx::tts::text_cleaner::TextCleaner;
+use ndarray_npy::NpzReader;
+use kittenx::utils::{compare_waveforms, AudioFormat, TrimOptions};
+use kittenx::onnx::{quantize_file, AccelerationProvider, Backend, OptimizationLevel, ProviderOptions, QuantizeOptions, SessionOptions};
+use kittenx::tts::markup::{speakable_text, speech_blocks};
+use kittenx::tts::phonemizer::PhonemizerBackend;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+mod config;
+mod inspect;
+mod repl;
 
 #[derive(Parser)]
 #[command(name = "kittenx")]
@@ -11,6 +24,141 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Never access the network; fail if model files are missing (also KITTENX_OFFLINE=1)
+    #[arg(long, global = true)]
+    offline: bool,
+}
+
+/// Inference backend and session tuning flags shared by commands that load the model
+#[derive(Args)]
+struct SessionArgs {
+    /// Inference runtime (default: onnxruntime when compiled in, else tract)
+    #[arg(long)]
+    backend: Option<Backend>,
+
+    /// Intra-op threads (defaults to ONNX Runtime's choice, usually all cores)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Inter-op threads, used with --parallel-execution
+    #[arg(long)]
+    inter_threads: Option<usize>,
+
+    /// Graph optimization level
+    #[arg(long)]
+    opt_level: Option<OptimizationLevel>,
+
+    /// Run independent graph branches in parallel
+    #[arg(long, overrides_with = "no_parallel_execution")]
+    parallel_execution: bool,
+
+    /// Run graph branches one at a time, overriding session.parallel_execution
+    #[arg(long, overrides_with = "parallel_execution")]
+    no_parallel_execution: bool,
+
+    /// Use deterministic kernels for reproducible output
+    #[arg(long, overrides_with = "no_deterministic")]
+    deterministic: bool,
+
+    /// Allow non-deterministic kernels, overriding session.deterministic
+    #[arg(long, overrides_with = "deterministic")]
+    no_deterministic: bool,
+
+    /// Number of inference sessions to load for concurrent requests
+    #[arg(long)]
+    sessions: Option<usize>,
+
+    /// GPU device index for CUDA, TensorRT, DirectML, ROCm and WebGPU
+    #[arg(long)]
+    device_id: Option<i32>,
+
+    /// Allow fp16 kernels (TensorRT, OpenVINO)
+    #[arg(long, overrides_with = "no_fp16")]
+    fp16: bool,
+
+    /// Keep fp16 kernels off, overriding session.fp16
+    #[arg(long, overrides_with = "fp16")]
+    no_fp16: bool,
+
+    /// Directory for cached TensorRT engines
+    #[arg(long)]
+    trt_engine_cache: Option<PathBuf>,
+
+    /// OpenVINO device type (e.g. CPU, GPU, NPU)
+    #[arg(long)]
+    openvino_device: Option<String>,
+}
+
+impl SessionArgs {
+    fn to_options(&self) -> SessionOptions {
+        SessionOptions {
+            intra_threads: self.threads,
+            inter_threads: self.inter_threads,
+            optimization_level: self.opt_level,
+            memory_pattern: None,
+            provider_options: ProviderOptions {
+                device_id: self.device_id,
+                tensorrt_engine_cache_path: self.trt_engine_cache.clone(),
+                fp16: self.fp16,
+                openvino_device_type: self.openvino_device.clone(),
+            },
+            deterministic_compute: self.deterministic,
+            parallel_execution: self.parallel_execution,
+            pool_size: self.sessions,
+            backend: self.backend,
+            // Replaced by the log level the model is loaded with
+            log_level: LogLevel::default(),
+        }
+    }
+}
+
+/// Output encoding and post-processing flags shared by commands that synthesize
+#[derive(Args)]
+struct AudioArgs {
+    /// Audio format: wav (32-bit float), wav16 or raw (little-endian f32)
+    #[arg(long, default_value = "wav")]
+    format: AudioFormat,
+
+    /// Keep leading and trailing silence
+    #[arg(long, overrides_with = "trim")]
+    no_trim: bool,
+
+    /// Trim leading and trailing silence, overriding audio.trim = false
+    #[arg(long, overrides_with = "no_trim")]
+    trim: bool,
+
+    /// Scale the output so its peak reaches this level (0 to 1)
+    #[arg(long)]
+    normalize_peak: Option<f32>,
+}
+
+impl AudioArgs {
+    fn post_processing(&self) -> PostProcessing {
+        PostProcessing {
+            trim: if self.no_trim { None } else { Some(TrimOptions::default()) },
+            normalize_peak: self.normalize_peak,
+        }
+    }
+}
+
+/// Markup cleaning flags shared by commands that read text
+#[derive(Args)]
+struct MarkupArgs {
+    /// Markup to strip from the text before speaking it
+    #[arg(long, default_value = "text")]
+    input_format: InputFormat,
+
+    /// Announce or skip code blocks in markdown and html input
+    #[arg(long, default_value = "announce")]
+    code_blocks: CodeBlocks,
+}
+
+impl MarkupArgs {
+    /// `text` with the markup stripped, for commands that don't synthesize
+    fn speakable_text(&self, text: &str) -> String {
+        speakable_text(text, self.input_format, self.code_blocks)
+    }
 }
 
 #[derive(Subcommand)]
@@ -33,52 +181,810 @@ enum Commands {
         #[arg(short, long, default_value = "1.0")]
         speed: f32,
         
-        /// Model directory path
-        #[arg(short, long, default_value = "./models")]
-        model_dir: PathBuf,
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
         
         /// Acceleration provider to use
         #[arg(short = 'p', long, default_value = "cpu")]
         provider: AccelerationProvider,
+
+        /// Phonemizer backend to use (defaults to espeak when built in)
+        #[arg(long)]
+        phonemizer: Option<PhonemizerBackend>,
+
+        /// espeak language code (en-us, de, ...), or auto to detect it from the text
+        #[arg(long, default_value = "en-us")]
+        language: String,
+
+        /// Pitch shift in semitones (-12 to 12)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        pitch: f32,
+
+        /// Volume gain (1.0 = unchanged)
+        #[arg(long, default_value = "1.0")]
+        volume: f32,
+
+        /// Resample the output to this rate in Hz (defaults to the model's rate)
+        #[arg(long)]
+        sample_rate: Option<u32>,
+
+        #[command(flatten)]
+        markup: MarkupArgs,
+
+        #[command(flatten)]
+        audio: AudioArgs,
+
+        #[command(flatten)]
+        session: SessionArgs,
     },
     
+    /// Generate one WAV file per line of a text file, using batched inference
+    Batch {
+        /// Text file with one utterance per line (blank lines are skipped). Markdown and
+        /// html input has one per paragraph, heading or list item instead.
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Directory to write 0001.wav, 0002.wav, ... into
+        #[arg(short, long)]
+        output_dir: PathBuf,
+
+        /// Voice to use for synthesis
+        #[arg(short, long, default_value = "expr-voice-5-m")]
+        voice: String,
+
+        /// Speech speed (1.0 = normal)
+        #[arg(short, long, default_value = "1.0")]
+        speed: f32,
+
+        /// Number of utterances per model run
+        #[arg(short, long, default_value = "8")]
+        batch_size: usize,
+
+        #[command(flatten)]
+        markup: MarkupArgs,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Acceleration provider to use
+        #[arg(short = 'p', long, default_value = "cpu")]
+        provider: AccelerationProvider,
+
+        /// Phonemizer backend to use (defaults to espeak when built in)
+        #[arg(long)]
+        phonemizer: Option<PhonemizerBackend>,
+
+        #[command(flatten)]
+        audio: AudioArgs,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
+    /// Load the model once and synthesize each line typed on stdin
+    Repl {
+        /// Directory the audio files are written to
+        #[arg(short, long, default_value = ".")]
+        output_dir: PathBuf,
+
+        /// Number of files to cycle through before overwriting the oldest (repl-01, repl-02, ...)
+        #[arg(long, default_value = "10")]
+        keep: usize,
+
+        /// Write the audio to stdout instead of files
+        #[arg(long)]
+        stdout: bool,
+
+        /// Initial voice (change with :voice)
+        #[arg(short, long, default_value = "expr-voice-5-m")]
+        voice: String,
+
+        /// Initial speech speed (change with :speed)
+        #[arg(short, long, default_value = "1.0")]
+        speed: f32,
+
+        /// Initial espeak language code, or auto (change with :language)
+        #[arg(long, default_value = "en-us")]
+        language: String,
+
+        /// Print the phonemes of each line (toggle with :phonemes)
+        #[arg(long)]
+        phonemes: bool,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Acceleration provider to use
+        #[arg(short = 'p', long, default_value = "cpu")]
+        provider: AccelerationProvider,
+
+        /// Phonemizer backend to use (defaults to espeak when built in)
+        #[arg(long)]
+        phonemizer: Option<PhonemizerBackend>,
+
+        #[command(flatten)]
+        markup: MarkupArgs,
+
+        // --format sets the initial format; change it with :format
+        #[command(flatten)]
+        audio: AudioArgs,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
     /// List available voices
     ListVoices {
-        /// Model directory path
-        #[arg(short, long, default_value = "./models")]
-        model_dir: PathBuf,
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
         
         /// Acceleration provider to use
         #[arg(short = 'p', long, default_value = "cpu")]
         provider: AccelerationProvider,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
+    /// Show model and execution provider information
+    Info {
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
+        
+        /// Acceleration provider to use
+        #[arg(short = 'p', long, default_value = "cpu")]
+        provider: AccelerationProvider,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
+    /// Measure latency, first-sentence latency, real-time factor and peak memory on a standard corpus
+    Bench {
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to use: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Voice to use for synthesis
+        #[arg(short, long, default_value = "expr-voice-5-m")]
+        voice: String,
+
+        /// Untimed passes over the corpus before measuring
+        #[arg(long, default_value = "2")]
+        warmup: usize,
+
+        /// Timed passes over the corpus
+        #[arg(short, long, default_value = "5")]
+        iterations: usize,
+
+        /// Also write the results as JSON to this file ("-" for stdout)
+        #[arg(long)]
+        json: Option<PathBuf>,
+
+        /// Acceleration provider to use
+        #[arg(short = 'p', long, default_value = "cpu")]
+        provider: AccelerationProvider,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
+    /// Quantize a model's weights to int8 and compare its output with the original
+    Quantize {
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+
+        /// Model to quantize: a registry id (e.g. nano-0.8, mini-0.8) or a HuggingFace repo (owner/repo[@revision])
+        #[arg(long, default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Where to write the quantized model (defaults to <model file>.int8.onnx in the current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Keep weights with fewer elements than this in fp32
+        #[arg(long, default_value = "1024")]
+        min_elements: usize,
+
+        /// Dequantize MatMul weights at load time instead of running integer MatMul kernels
+        #[arg(long)]
+        weight_only: bool,
+
+        /// Skip comparing the quantized model's output with the original
+        #[arg(long)]
+        no_check: bool,
+
+        /// Voice used for the quality check
+        #[arg(short, long, default_value = "expr-voice-5-m")]
+        voice: String,
+
+        /// Acceleration provider used for the quality check
+        #[arg(short = 'p', long, default_value = "cpu")]
+        provider: AccelerationProvider,
+
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+
+    /// Manage downloaded models
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommand,
+    },
+
+    /// Show the sentences, language and IPA phonemes for text, without running the model
+    Phonemize {
+        /// Text to phonemize (read from stdin if omitted)
+        #[arg(short, long)]
+        text: Option<String>,
+
+        /// espeak language code (en-us, de, ...), or auto to detect it per sentence
+        #[arg(long, default_value = "en-us")]
+        language: String,
+
+        #[command(flatten)]
+        markup: MarkupArgs,
+
+        /// Phonemizer backend to use (defaults to espeak when built in)
+        #[arg(long)]
+        phonemizer: Option<PhonemizerBackend>,
+
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the token ids and symbols for IPA phonemes, and any characters the model drops
+    Tokenize {
+        /// IPA phonemes, e.g. from `kittenx phonemize` (read from stdin if omitted).
+        /// They are tokenized as given: markup cleaning would delete IPA characters.
+        #[arg(short, long)]
+        text: Option<String>,
+
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Inspect CLI defaults from kittenx.toml, the user config file and KITTENX_* variables
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
     },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print every setting with its effective value and where it comes from
+    Show,
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// List known models and whether they are downloaded
+    List {
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+    },
+
+    /// Download a model without synthesizing anything
+    Download {
+        /// Registry id or HuggingFace repo (owner/repo[@revision])
+        #[arg(default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+    },
+
+    /// Check that a downloaded model's files are intact
+    Verify {
+        /// Registry id or HuggingFace repo (owner/repo[@revision])
+        #[arg(default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+    },
+
+    /// Delete a downloaded model
+    Remove {
+        /// Registry id or HuggingFace repo (owner/repo[@revision])
+        model: String,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+    },
+
+    /// Show model metadata: ONNX inputs/outputs, opset, voices, vocabulary and sample rate
+    Info {
+        /// Registry id or HuggingFace repo (owner/repo[@revision])
+        #[arg(default_value = DEFAULT_MODEL_ID)]
+        model: String,
+
+        /// Model directory path (defaults to the shared cache, see KITTENX_CACHE)
+        #[arg(short, long)]
+        model_dir: Option<PathBuf>,
+    },
+}
+
+fn model_location(model_dir: Option<PathBuf>) -> ModelLocation {
+    match model_dir {
+        Some(dir) => ModelLocation::Dir(dir),
+        None => ModelLocation::default(),
+    }
+}
+
+fn resolve_model(id: &str, offline: bool) -> Result<ModelSpec> {
+    let spec = ModelSpec::resolve(id)?;
+    Ok(if offline { spec.offline(true) } else { spec })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn load_model(
+    model_dir: Option<PathBuf>,
+    model: &str,
+    offline: bool,
+    provider: AccelerationProvider,
+    phonemizer: Option<PhonemizerBackend>,
+    session: &SessionArgs,
+    post_processing: PostProcessing,
+    log_level: LogLevel,
+) -> Result<KittenTTS> {
+    let mut builder = KittenTTS::builder()
+        .model(resolve_model(model, offline)?)
+        .provider(provider)
+        .session_options(session.to_options())
+        .post_processing(post_processing)
+        .log_level(log_level);
+    if let Some(dir) = model_dir {
+        builder = builder.model_dir(dir);
+    }
+    if let Some(backend) = phonemizer {
+        builder = builder.phonemizer(backend.build()?);
+    }
+    builder.build_async().await
+}
+
+/// Sentences synthesized by both models in the `quantize` quality check
+const QUALITY_CHECK_SENTENCES: &[&str] = &[
+    "The quick brown fox jumps over the lazy dog.",
+    "Quantized models trade a little accuracy for a much smaller download.",
+    "How are you today? I hope everything is going well!",
+];
+
+/// Envelope correlation below which the quantized model is reported as audibly different
+const MIN_ENVELOPE_CORRELATION: f32 = 0.9;
+
+#[allow(clippy::too_many_arguments)]
+async fn run_quantize(
+    model_dir: Option<PathBuf>,
+    model: &str,
+    output: Option<PathBuf>,
+    options: QuantizeOptions,
+    check: bool,
+    voice: &str,
+    offline: bool,
+    provider: AccelerationProvider,
+    session: &SessionArgs,
+) -> Result<()> {
+    let spec = resolve_model(model, offline)?;
+    let (files, _) = model_location(model_dir).fetch(&spec).await?;
+    let output = output.unwrap_or_else(|| {
+        let stem = files.model.file_stem().unwrap_or_default().to_string_lossy();
+        PathBuf::from(format!("{}.int8.onnx", stem))
+    });
+    anyhow::ensure!(output != files.model, "Refusing to overwrite the original model {}", files.model.display());
+
+    eprintln!("Quantizing {} to {}", files.model.display(), output.display());
+    let report = quantize_file(&files.model, &output, &options)?;
+    eprintln!(
+        "Quantized {} parameters: {} MatMuls on int8 kernels, {} weights dequantized at load time, {} small weights kept in fp32",
+        report.quantized_parameters, report.integer_matmuls, report.dequantized_weights, report.fp32_weights
+    );
+    let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    eprintln!(
+        "Size: {:.1} MB -> {:.1} MB ({:.0}%)",
+        mb(report.input_bytes),
+        mb(report.output_bytes),
+        100.0 * report.output_bytes as f64 / report.input_bytes.max(1) as f64
+    );
+    if !check {
+        return Ok(());
+    }
+
+    eprintln!("Comparing outputs on {} sentences with voice {}", QUALITY_CHECK_SENTENCES.len(), voice);
+    let original = KittenTTS::from_files(&files, provider, session.to_options())?;
+    let mut quantized_files = files.clone();
+    quantized_files.model = output.clone();
+    let quantized = KittenTTS::from_files(&quantized_files, provider, session.to_options())
+        .with_context(|| format!("The quantized model {} failed to load", output.display()))?;
+
+    let mut worst_correlation = f32::INFINITY;
+    let (mut original_time, mut quantized_time) = (Duration::ZERO, Duration::ZERO);
+    for text in QUALITY_CHECK_SENTENCES {
+        let start = Instant::now();
+        let reference = original.generate_outputs(text, voice, 1.0)?;
+        original_time += start.elapsed();
+        let start = Instant::now();
+        let candidate = quantized.generate_outputs(text, voice, 1.0)?;
+        quantized_time += start.elapsed();
+
+        let reference: Vec<f32> = reference.waveform.iter().copied().collect();
+        let candidate: Vec<f32> = candidate.waveform.iter().copied().collect();
+        let comparison = compare_waveforms(&reference, &candidate, original.sample_rate());
+        worst_correlation = worst_correlation.min(comparison.envelope_correlation);
+        eprintln!(
+            "  SNR {:>6.1} dB, envelope correlation {:.3}, length {:.2}x  \"{}\"",
+            comparison.snr_db, comparison.envelope_correlation, comparison.length_ratio, text
+        );
+    }
+    eprintln!(
+        "Inference time: original {:.2}s, quantized {:.2}s",
+        original_time.as_secs_f64(),
+        quantized_time.as_secs_f64()
+    );
+    if worst_correlation < MIN_ENVELOPE_CORRELATION {
+        eprintln!(
+            "Warning: the quantized model sounds noticeably different (envelope correlation {:.3} < {}); \
+             try a higher --min-elements or --weight-only",
+            worst_correlation, MIN_ENVELOPE_CORRELATION
+        );
+    } else {
+        eprintln!("Quality check passed");
+    }
+    Ok(())
+}
+
+async fn run_models_command(action: ModelsCommand, offline: bool) -> Result<()> {
+    match action {
+        ModelsCommand::List { model_dir } => {
+            let location = model_location(model_dir);
+            println!("Models in {}:", location);
+            for entry in REGISTRY {
+                let spec = ModelSpec::resolve(entry.id)?;
+                let status = match location.find(&spec) {
+                    Some((files, _)) => format!("downloaded: {}", files.model.display()),
+                    None => "not downloaded".to_string(),
+                };
+                let default = if spec.is_default() { " (default)" } else { "" };
+                println!("  {:<14} {:<5} {}{}", entry.id, entry.precision, entry.description, default);
+                println!("  {:<14} {}", "", status);
+            }
+        }
+
+        ModelsCommand::Download { model, model_dir } => {
+            let spec = resolve_model(&model, offline)?;
+            let (files, _) = model_location(model_dir).fetch(&spec).await?;
+            println!("Model {} is available:", spec.id);
+            println!("  {}", files.model.display());
+            println!("  {}", files.voices.display());
+        }
+
+        ModelsCommand::Verify { model, model_dir } => {
+            let spec = resolve_model(&model, offline)?;
+            let location = model_location(model_dir);
+            let (files, config) = location
+                .find(&spec)
+                .ok_or_else(|| anyhow::anyhow!("Model {} is not downloaded in {}", spec.id, location))?;
+
+            let checks = verify_model(&spec, &files, &config).await;
+            for check in &checks {
+                println!("{}", check);
+            }
+            let failed = checks.iter().filter(|c| !c.is_ok()).count();
+            if failed > 0 {
+                anyhow::bail!("{} of {} files failed verification; run `kittenx models remove {}` and download again", failed, checks.len(), model);
+            }
+            println!("Model {} verified", spec.id);
+        }
+
+        ModelsCommand::Remove { model, model_dir } => {
+            let spec = ModelSpec::resolve(&model)?;
+            let location = model_location(model_dir);
+            let removed = location.remove(&spec)?;
+            if removed.is_empty() {
+                println!("Model {} is not downloaded in {}", spec.id, location);
+            }
+            for path in removed {
+                println!("Removed {}", path.display());
+            }
+        }
+
+        ModelsCommand::Info { model, model_dir } => {
+            let spec = resolve_model(&model, offline)?;
+            let (files, config) = model_location(model_dir).fetch(&spec).await?;
+            let summary = ModelSummary::load(&files.model)?;
+
+            println!("Model: {} ({}@{})", spec.id, spec.repo, spec.revision);
+            if let Some(name) = &config.name {
+                println!("Name: {} {}", name, config.version.as_deref().unwrap_or(""));
+            }
+            if let Some(model_type) = &config.model_type {
+                println!("Type: {}", model_type);
+            }
+            let size_mb = std::fs::metadata(&files.model)?.len() as f64 / (1024.0 * 1024.0);
+            println!("Model file: {} ({:.1} MB)", files.model.display(), size_mb);
+            println!("Voices file: {}", files.voices.display());
+            println!("ONNX IR version: {}", summary.ir_version);
+            if !summary.producer_name.is_empty() {
+                println!("Producer: {} {}", summary.producer_name, summary.producer_version);
+            }
+            let opsets: Vec<String> = summary.opsets.iter().map(|(domain, v)| format!("{} {}", domain, v)).collect();
+            println!("Opsets: {}", opsets.join(", "));
+            println!("Inputs:");
+            for input in &summary.inputs {
+                println!("  {}", input);
+            }
+            println!("Outputs:");
+            for output in &summary.outputs {
+                println!("  {}", output);
+            }
+            let by_type: Vec<String> = summary
+                .parameters_by_type
+                .iter()
+                .map(|(t, n)| format!("{} {}", n, elem_type_name(*t)))
+                .collect();
+            println!("Parameters: {} ({})", summary.parameter_count, by_type.join(", "));
+            println!("Sample rate: {} Hz", config.sample_rate.unwrap_or(24000));
+            println!("Vocabulary size: {}", TextCleaner::new().vocab_size());
+
+            let mut npz = NpzReader::new(std::fs::File::open(&files.voices)?)?;
+            let mut names = npz.names()?;
+            names.sort();
+            println!("Voices ({}):", names.len());
+            for name in names {
+                let shape = npz
+                    .by_name::<ndarray::OwnedRepr<f32>, ndarray::IxDyn>(&name)
+                    .map(|array| format!("{:?}", array.shape()))
+                    .unwrap_or_else(|e| format!("unreadable: {}", e));
+                let aliases: Vec<&str> = config
+                    .voice_aliases
+                    .iter()
+                    .filter(|(_, target)| **target == name)
+                    .map(|(alias, _)| alias.as_str())
+                    .collect();
+                if aliases.is_empty() {
+                    println!("  {} {}", name, shape);
+                } else {
+                    println!("  {} {} (alias: {})", name, shape, aliases.join(", "));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // Config files and KITTENX_* variables replace the built-in argument defaults. This runs
+    // before the runtime starts, as export_env must not change the environment under other threads.
+    let defaults = config::Defaults::load()?;
+    defaults.export_env();
+    let matches = defaults.apply(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    tokio::runtime::Runtime::new()?.block_on(run(cli, &defaults))
+}
+
+async fn run(cli: Cli, defaults: &config::Defaults) -> Result<()> {
     match cli.command {
-        Commands::Generate { text, output, voice, speed, model_dir, provider } => {
+        Commands::Generate {
+            text, output, voice, speed, model_dir, model, provider, phonemizer, language, pitch, volume, sample_rate, markup,
+            audio, session,
+        } => {
             println!("Loading KittenTTS model...");
-            let tts = KittenTTS::with_provider(&model_dir, provider).await?;
+            let tts =
+                load_model(model_dir, &model, cli.offline, provider, phonemizer, &session, audio.post_processing(), LogLevel::Debug)
+                    .await?;
             
             println!("Generating speech for: \"{}\"", text);
             println!("Using voice: {}", voice);
             println!("Speed: {}", speed);
             
-            tts.generate_to_file(&text, &voice, speed, &output)?;
+            let mut request = SynthesisRequest::new(text)
+                .voice(voice)
+                .speed(speed)
+                .language(language)
+                .pitch(pitch)
+                .volume(volume)
+                .input_format(markup.input_format)
+                .code_blocks(markup.code_blocks);
+            if let Some(rate) = sample_rate {
+                request = request.output_sample_rate(rate);
+            }
+            let speech = tts.synthesize(&request)?;
+            audio.format.save(&speech.samples, speech.sample_rate, &output)?;
+            println!("Audio saved to {}", output.display());
         }
         
-        Commands::ListVoices { model_dir, provider } => {
+        Commands::Batch {
+            input, output_dir, voice, speed, batch_size, markup, model_dir, model, provider, phonemizer, audio, session,
+        } => {
+            let contents = std::fs::read_to_string(&input)?;
+            let lines: Vec<String> = match markup.input_format {
+                InputFormat::Text => contents.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect(),
+                format => speech_blocks(&contents, format, markup.code_blocks).into_iter().map(|block| block.text).collect(),
+            };
+            std::fs::create_dir_all(&output_dir)?;
+
+            println!("Loading KittenTTS model...");
+            let tts =
+                load_model(model_dir, &model, cli.offline, provider, phonemizer, &session, audio.post_processing(), LogLevel::Debug)
+                    .await?;
+
+            println!("Generating {} utterances in batches of {}", lines.len(), batch_size.max(1));
+            let mut index = 0;
+            for chunk in lines.chunks(batch_size.max(1)) {
+                let requests: Vec<(&str, &str, f32)> = chunk.iter().map(|text| (text.as_str(), voice.as_str(), speed)).collect();
+                for samples in tts.generate_batch(&requests)? {
+                    index += 1;
+                    let path = output_dir.join(format!("{:04}.{}", index, audio.format.extension()));
+                    audio.format.save(&samples, tts.sample_rate(), &path)?;
+                    println!("Audio saved to {}", path.display());
+                }
+            }
+        }
+
+        Commands::Repl {
+            output_dir, keep, stdout, voice, speed, language, phonemes, model_dir, model, provider, phonemizer, markup, audio,
+            session,
+        } => {
+            eprintln!("Loading KittenTTS model...");
+            // Keep stdout free for audio when --stdout is set
+            let log_level = if stdout { LogLevel::Quiet } else { LogLevel::Info };
+            let tts =
+                load_model(model_dir, &model, cli.offline, provider, phonemizer, &session, audio.post_processing(), log_level).await?;
+
+            let output = if stdout { repl::Output::Stdout } else { repl::Output::Files { dir: output_dir, keep } };
+            let settings = repl::Settings {
+                voice,
+                speed,
+                language,
+                pitch: 0.0,
+                format: audio.format,
+                phonemes,
+                input_format: markup.input_format,
+                code_blocks: markup.code_blocks,
+            };
+            repl::run(&tts, settings, output)?;
+        }
+
+        Commands::ListVoices { model_dir, model, provider, session } => {
             println!("Loading KittenTTS model...");
-            let tts = KittenTTS::with_provider(&model_dir, provider).await?;
+            let tts = load_model(model_dir, &model, cli.offline, provider, None, &session, PostProcessing::default(), LogLevel::Debug).await?;
             
+            println!("Execution provider: {}", tts.provider_status());
             println!("Available voices:");
             for voice in tts.available_voices() {
                 println!("  - {}", voice);
             }
         }
+
+        Commands::Info { model_dir, model, provider, session } => {
+            println!("Loading KittenTTS model...");
+            let tts = load_model(model_dir, &model, cli.offline, provider, None, &session, PostProcessing::default(), LogLevel::Debug).await?;
+            let status = tts.provider_status();
+
+            println!("Model: {}", tts.model_id());
+            println!("Model file: {}", tts.model_files().model.display());
+            println!("Voices file: {}", tts.model_files().voices.display());
+            println!("Sample rate: {} Hz", tts.sample_rate());
+            println!("Requested provider: {}", status.requested);
+            println!("Active provider: {}", status.active);
+            if let Some(reason) = &status.fallback_reason {
+                println!("Fallback reason: {}", reason);
+            }
+            let compiled: Vec<&str> = AccelerationProvider::ALL
+                .iter()
+                .filter(|p| p.is_compiled_in())
+                .map(|p| p.name())
+                .collect();
+            println!("Providers compiled in: {}", compiled.join(", "));
+            println!("{}", tts.signature());
+            println!("Voices: {}", tts.available_voices().join(", "));
+        }
+
+        Commands::Bench { model_dir, model, voice, warmup, iterations, json, provider, session } => {
+            // With `--json -` stdout carries only the JSON report
+            let json_to_stdout = json.as_ref().is_some_and(|path| path.as_os_str() == "-");
+            let status = |message: &dyn std::fmt::Display| {
+                if json_to_stdout {
+                    eprintln!("{}", message);
+                } else {
+                    println!("{}", message);
+                }
+            };
+
+            status(&"Loading KittenTTS model...");
+            let start = Instant::now();
+            // Quiet, so per-request logging stays out of the timings and the output
+            let tts =
+                load_model(model_dir, &model, cli.offline, provider, None, &session, PostProcessing::default(), LogLevel::Quiet)
+                    .await?;
+            let load_seconds = start.elapsed().as_secs_f64();
+
+            let options = BenchOptions { voice, warmup, iterations, ..Default::default() };
+            status(&format!("Benchmarking {} texts: {} warmup and {} timed passes", CORPUS.len(), warmup, iterations.max(1)));
+            let mut report = run_benchmark(&tts, &session.to_options(), &options)?;
+            report.load_seconds = Some(load_seconds);
+
+            status(&format!("\n{}", report));
+            if let Some(path) = json {
+                let json = serde_json::to_string_pretty(&report)?;
+                if json_to_stdout {
+                    println!("{}", json);
+                } else {
+                    std::fs::write(&path, json)?;
+                    println!("Results written to {}", path.display());
+                }
+            }
+        }
+
+        Commands::Quantize { model_dir, model, output, min_elements, weight_only, no_check, voice, provider, session } => {
+            let options = QuantizeOptions { min_elements, integer_matmul: !weight_only };
+            run_quantize(model_dir, &model, output, options, !no_check, &voice, cli.offline, provider, &session).await?;
+        }
+
+        Commands::Models { action } => run_models_command(action, cli.offline).await?,
+
+        Commands::Phonemize { text, language, markup, phonemizer, json } => {
+            let text = markup.speakable_text(&inspect::read_input(text)?);
+            let sentences = inspect::phonemize(&text, &language, phonemizer)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&sentences)?);
+            } else {
+                inspect::print_phonemes(&sentences);
+            }
+        }
+
+        Commands::Tokenize { text, json } => {
+            let tokenization = inspect::tokenize(inspect::read_input(text)?.trim());
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tokenization)?);
+            } else {
+                inspect::print_tokens(&tokenization);
+            }
+        }
+
+        Commands::Config { action: ConfigCommand::Show } => {
+            let mut command = Cli::command();
+            command.build();
+            defaults.show(&command);
+        }
     }
     
     Ok(())
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Contents of a KittenTTS `config.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default, rename = "type")]
+    pub model_type: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// ONNX model file name, relative to the model directory
+    pub model_file: String,
+    /// Voice embeddings NPZ file name, relative to the model directory
+    #[serde(default = "default_voices_file")]
+    pub voices: String,
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    /// Friendly voice names mapped to the NPZ array names
+    #[serde(default)]
+    pub voice_aliases: HashMap<String, String>,
+}
+
+fn default_voices_file() -> String {
+    "voices.npz".to_string()
+}
+
+impl ModelConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read model config {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse model config {}", path.display()))
+    }
+
+    /// Parse the contents of a `config.json`
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
@@ -0,0 +1,326 @@
+#[cfg(feature = "download")]
+use super::files::missing_files_message;
+use super::{ModelConfig, ModelFiles, ModelSpec};
+#[cfg(feature = "download")]
+use crate::utils::download::{advertised_sha256, remote_headers};
+#[cfg(feature = "download")]
+use crate::utils::Downloader;
+use anyhow::{Context, Result};
+use hf_hub::{Cache, Repo, RepoType};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the shared model cache directory
+pub const CACHE_ENV: &str = "KITTENX_CACHE";
+/// Environment variable pointing downloads at a HuggingFace mirror
+pub const ENDPOINT_ENV: &str = "HF_ENDPOINT";
+
+const DEFAULT_ENDPOINT: &str = "https://huggingface.co";
+
+/// Shared cache directory, in the HuggingFace hub layout so it is shared with other HF tools.
+///
+/// `KITTENX_CACHE` if set, otherwise `$HF_HOME/hub`, otherwise `huggingface/hub` in the
+/// platform cache directory (`$XDG_CACHE_HOME` or `~/.cache` on Linux).
+pub fn default_cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CACHE_ENV).filter(|v| !v.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    if let Some(home) = std::env::var_os("HF_HOME").filter(|v| !v.is_empty()) {
+        return PathBuf::from(home).join("hub");
+    }
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("huggingface")
+        .join("hub")
+}
+
+/// Base URL of the HuggingFace Hub, honouring `HF_ENDPOINT`
+pub fn hub_endpoint() -> String {
+    std::env::var(ENDPOINT_ENV)
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+}
+
+/// Where a model's files are stored locally
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelLocation {
+    /// Shared HuggingFace-style cache (`models--owner--repo/snapshots/<commit>/...`),
+    /// pinned to the model's revision
+    Cache(PathBuf),
+    /// Plain directory holding the files directly (the default model) or in a
+    /// subdirectory named after the model id
+    Dir(PathBuf),
+}
+
+impl Default for ModelLocation {
+    fn default() -> Self {
+        ModelLocation::Cache(default_cache_dir())
+    }
+}
+
+impl ModelLocation {
+    /// Make sure the model is available locally, downloading it unless the spec is offline
+    #[cfg(feature = "download")]
+    pub async fn fetch(&self, model: &ModelSpec) -> Result<(ModelFiles, ModelConfig)> {
+        match self {
+            ModelLocation::Cache(cache_dir) => fetch_cached(model, cache_dir).await,
+            ModelLocation::Dir(model_dir) => fetch_to_dir(model, model_dir).await,
+        }
+    }
+
+    /// Blocking `fetch` for synchronous callers. Files already present are used directly;
+    /// missing ones are downloaded on a private runtime, so this must not be called from
+    /// async code. Without the `download` feature missing files are an error.
+    pub fn fetch_blocking(&self, model: &ModelSpec) -> Result<(ModelFiles, ModelConfig)> {
+        if let Some(found) = self.find(model) {
+            return Ok(found);
+        }
+
+        #[cfg(feature = "download")]
+        {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("Failed to start a runtime for the download")?;
+            runtime.block_on(self.fetch(model))
+        }
+        #[cfg(not(feature = "download"))]
+        anyhow::bail!(
+            "Model {} is not in the {} and kittenx was built without the `download` feature; \
+             fetch it with `kittenx models download {}` or load explicit files with `KittenTTS::from_files`",
+            model.id,
+            self,
+            model.id
+        )
+    }
+
+    /// Files of the model if all of them are already present, without touching the network
+    pub fn find(&self, model: &ModelSpec) -> Option<(ModelFiles, ModelConfig)> {
+        let lookup = |file: &str| -> Option<PathBuf> {
+            match self {
+                ModelLocation::Cache(cache_dir) => cache_repo(model, cache_dir).get(file),
+                ModelLocation::Dir(model_dir) => {
+                    Some(model.local_dir(model_dir).join(file)).filter(|p| p.exists())
+                }
+            }
+        };
+        let config_path = lookup("config.json")?;
+        let config = ModelConfig::load(&config_path).ok()?;
+        let files = ModelFiles::new(lookup(&config.model_file)?, lookup(&config.voices)?)
+            .with_config(config_path);
+        Some((files, config))
+    }
+
+    /// Delete a model's local files, returning what was removed.
+    /// In the cache this drops every cached revision of the repository.
+    pub fn remove(&self, model: &ModelSpec) -> Result<Vec<PathBuf>> {
+        let mut removed = Vec::new();
+        match self {
+            ModelLocation::Cache(cache_dir) => {
+                let repo_dir = cache_dir.join(hub_repo(model).folder_name());
+                if repo_dir.exists() {
+                    std::fs::remove_dir_all(&repo_dir)
+                        .with_context(|| format!("Failed to remove {}", repo_dir.display()))?;
+                    removed.push(repo_dir);
+                }
+            }
+            ModelLocation::Dir(model_dir) if model.is_default() => {
+                // The default model shares the directory root with other models' subdirectories
+                let config = ModelConfig::load(&model_dir.join("config.json")).ok();
+                let mut names = vec!["config.json".to_string()];
+                if let Some(config) = config {
+                    names.push(config.model_file);
+                    names.push(config.voices);
+                }
+                for name in names {
+                    let path = model_dir.join(name);
+                    if path.exists() {
+                        std::fs::remove_file(&path)
+                            .with_context(|| format!("Failed to remove {}", path.display()))?;
+                        removed.push(path);
+                    }
+                }
+            }
+            ModelLocation::Dir(model_dir) => {
+                let local_dir = model.local_dir(model_dir);
+                if local_dir.exists() {
+                    std::fs::remove_dir_all(&local_dir)
+                        .with_context(|| format!("Failed to remove {}", local_dir.display()))?;
+                    removed.push(local_dir);
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+impl std::fmt::Display for ModelLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelLocation::Cache(dir) => write!(f, "cache {}", dir.display()),
+            ModelLocation::Dir(dir) => write!(f, "directory {}", dir.display()),
+        }
+    }
+}
+
+fn hub_repo(model: &ModelSpec) -> Repo {
+    Repo::with_revision(model.repo.clone(), RepoType::Model, model.revision.clone())
+}
+
+fn cache_repo(model: &ModelSpec, cache_dir: &Path) -> hf_hub::CacheRepo {
+    Cache::new(cache_dir.to_path_buf()).repo(hub_repo(model))
+}
+
+#[cfg(feature = "download")]
+async fn fetch_cached(model: &ModelSpec, cache_dir: &Path) -> Result<(ModelFiles, ModelConfig)> {
+    if model.offline {
+        let cached = cache_repo(model, cache_dir);
+        let Some(config_path) = cached.get("config.json") else {
+            anyhow::bail!(
+                "{}\n(offline mode: {} revision {} is not in the cache at {})",
+                missing_files_message(&[Path::new("config.json")]),
+                model.repo,
+                model.revision,
+                cache_dir.display()
+            );
+        };
+        let config = ModelConfig::load(&config_path)?;
+        let model_file = cached.get(&config.model_file);
+        let voices = cached.get(&config.voices);
+        if model_file.is_none() || voices.is_none() {
+            let mut missing = Vec::new();
+            if model_file.is_none() {
+                missing.push(Path::new(&config.model_file));
+            }
+            if voices.is_none() {
+                missing.push(Path::new(&config.voices));
+            }
+            anyhow::bail!(
+                "{}\n(offline mode: not in the cache at {})",
+                missing_files_message(&missing),
+                cache_dir.display()
+            );
+        }
+        let files = ModelFiles::new(model_file.unwrap(), voices.unwrap()).with_config(config_path);
+        return Ok((files, config));
+    }
+
+    // config.json names the remaining files, so fetch it first
+    let config_path = fetch_hub_file(model, cache_dir, "config.json").await?;
+    let config = ModelConfig::load(&config_path)?;
+    let model_file = fetch_hub_file(model, cache_dir, &config.model_file).await?;
+    let voices = fetch_hub_file(model, cache_dir, &config.voices).await?;
+
+    Ok((ModelFiles::new(model_file, voices).with_config(config_path), config))
+}
+
+/// Fetch one file into the hub cache layout, as hf-hub does, but through `Downloader` so
+/// it is retried, resumed and verified: the content goes to `blobs/<etag>`,
+/// `snapshots/<commit>/<file>` links to it and `refs/<revision>` records the commit.
+#[cfg(feature = "download")]
+async fn fetch_hub_file(model: &ModelSpec, cache_dir: &Path, file: &str) -> Result<PathBuf> {
+    let cached = cache_repo(model, cache_dir);
+    if let Some(path) = cached.get(file) {
+        return Ok(path);
+    }
+
+    eprintln!("Fetching {}@{}/{}", model.repo, model.revision, file);
+    let url = model.file_url(file);
+    let headers = remote_headers(&url)
+        .await
+        .with_context(|| format!("Failed to fetch {} from {}@{}", file, model.repo, model.revision))?;
+    let header = |name: &str| -> Result<String> {
+        let value = headers.get(name).with_context(|| format!("{} sent no {} header for {}", hub_endpoint(), name, file))?;
+        Ok(value.to_str()?.trim_start_matches("W/").replace('"', ""))
+    };
+    let commit = header("x-repo-commit")?;
+    let etag = header("x-linked-etag").or_else(|_| header("etag"))?;
+
+    let repo_dir = cache_dir.join(hub_repo(model).folder_name());
+    let blob = repo_dir.join("blobs").join(&etag);
+    if !blob.exists() {
+        let expected = model.known_sha256(file).map(str::to_string).or_else(|| advertised_sha256(&headers));
+        Downloader::new()
+            .with_required_checksum(model.is_registered())
+            .download(&url, &blob, expected.as_deref())
+            .await?;
+    }
+
+    let pointer = repo_dir.join("snapshots").join(&commit).join(file);
+    if !pointer.exists() {
+        let parent = pointer.parent().expect("pointer path has a parent");
+        std::fs::create_dir_all(parent)?;
+        link_blob(&blob, &pointer, file)
+            .with_context(|| format!("Failed to link {} to {}", pointer.display(), blob.display()))?;
+    }
+    cached.create_ref(&commit)?;
+    Ok(pointer)
+}
+
+/// Point `snapshots/<commit>/<file>` at its blob with a relative symlink, or a copy where
+/// symlinks aren't available
+#[cfg(feature = "download")]
+fn link_blob(blob: &Path, pointer: &Path, file: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        // Up from the pointer's directory to the repository directory
+        let mut target = PathBuf::new();
+        for _ in 0..Path::new(file).components().count() + 1 {
+            target.push("..");
+        }
+        target.push("blobs");
+        target.push(blob.file_name().unwrap_or_default());
+        std::os::unix::fs::symlink(target, pointer)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+        std::fs::copy(blob, pointer).map(|_| ())
+    }
+}
+
+#[cfg(feature = "download")]
+async fn fetch_to_dir(model: &ModelSpec, model_dir: &Path) -> Result<(ModelFiles, ModelConfig)> {
+    let local_dir = model.local_dir(model_dir);
+
+    // config.json names the remaining files, so fetch it first
+    let config_path = local_dir.join("config.json");
+    if !config_path.exists() {
+        if model.offline {
+            anyhow::bail!(
+                "{}\n(offline mode: the model and voices files are named by config.json)",
+                missing_files_message(&[&config_path])
+            );
+        }
+        tokio::fs::create_dir_all(&local_dir).await?;
+        download_model_file(model, "config.json", &config_path).await?;
+    }
+    let config = ModelConfig::load(&config_path)?;
+
+    let files = ModelFiles::new(local_dir.join(&config.model_file), local_dir.join(&config.voices))
+        .with_config(&config_path);
+
+    if model.offline {
+        files.ensure_present().context("Offline mode is enabled, not downloading")?;
+    } else {
+        if !files.model.exists() {
+            download_model_file(model, &config.model_file, &files.model).await?;
+        }
+        if !files.voices.exists() {
+            download_model_file(model, &config.voices, &files.voices).await?;
+        }
+    }
+
+    Ok((files, config))
+}
+
+/// Download one file of the model repository, checked against the registry's SHA-256 if
+/// it has one. Registry models fail rather than install a file no hash is known for.
+#[cfg(feature = "download")]
+async fn download_model_file(model: &ModelSpec, file: &str, path: &Path) -> Result<()> {
+    Downloader::new()
+        .with_required_checksum(model.is_registered())
+        .download(&model.file_url(file), path, model.known_sha256(file))
+        .await
+}
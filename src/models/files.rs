@@ -0,0 +1,60 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that forbids network access when set to `1`, `true` or `yes`
+pub const OFFLINE_ENV: &str = "KITTENX_OFFLINE";
+
+/// Whether `KITTENX_OFFLINE` asks for offline mode
+pub fn offline_from_env() -> bool {
+    std::env::var(OFFLINE_ENV)
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Paths of the files that make up a model on disk
+#[derive(Debug, Clone)]
+pub struct ModelFiles {
+    /// `config.json`; optional when loading from explicit paths
+    pub config: Option<PathBuf>,
+    pub model: PathBuf,
+    pub voices: PathBuf,
+}
+
+impl ModelFiles {
+    pub fn new(model: impl Into<PathBuf>, voices: impl Into<PathBuf>) -> Self {
+        Self {
+            config: None,
+            model: model.into(),
+            voices: voices.into(),
+        }
+    }
+
+    pub fn with_config(mut self, config: impl Into<PathBuf>) -> Self {
+        self.config = Some(config.into());
+        self
+    }
+
+    /// Files that do not exist on disk
+    pub fn missing(&self) -> Vec<&Path> {
+        self.config
+            .iter()
+            .chain([&self.model, &self.voices])
+            .map(PathBuf::as_path)
+            .filter(|path| !path.exists())
+            .collect()
+    }
+
+    /// Fail with a list of every missing file
+    pub fn ensure_present(&self) -> Result<()> {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            anyhow::bail!("{}", missing_files_message(&missing));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn missing_files_message(missing: &[&Path]) -> String {
+    let list: Vec<String> = missing.iter().map(|p| format!("  - {}", p.display())).collect();
+    format!("Model files are missing:\n{}", list.join("\n"))
+}
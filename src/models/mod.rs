@@ -0,0 +1,13 @@
+pub mod config;
+pub mod fetch;
+pub mod files;
+pub mod registry;
+#[cfg(feature = "download")]
+pub mod verify;
+
+pub use config::ModelConfig;
+pub use fetch::{default_cache_dir, hub_endpoint, ModelLocation, CACHE_ENV, ENDPOINT_ENV};
+pub use files::{offline_from_env, ModelFiles, OFFLINE_ENV};
+pub use registry::{ModelSpec, Precision, RegistryEntry, DEFAULT_MODEL_ID, REGISTRY};
+#[cfg(feature = "download")]
+pub use verify::{verify_model, CheckStatus, FileCheck};
@@ -0,0 +1,208 @@
+use super::fetch::hub_endpoint;
+use super::files::offline_from_env;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Model used when no `--model` is given
+pub const DEFAULT_MODEL_ID: &str = "nano-0.1";
+
+/// Weight precision of a published model variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Fp32,
+    Int8,
+}
+
+impl std::fmt::Display for Precision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Precision::Fp32 => write!(f, "fp32"),
+            Precision::Int8 => write!(f, "int8"),
+        }
+    }
+}
+
+/// A known KittenTTS release on the HuggingFace Hub
+#[derive(Debug, Clone, Copy)]
+pub struct RegistryEntry {
+    pub id: &'static str,
+    pub repo: &'static str,
+    /// Commit the files are fetched from, so a push upstream can't change what users get
+    pub revision: &'static str,
+    pub precision: Precision,
+    pub description: &'static str,
+    /// SHA-256 of the repository's files, checked after downloading in preference to the
+    /// hash the server advertises
+    pub sha256: &'static [(&'static str, &'static str)],
+}
+
+/// Known KittenML releases. Any other `owner/repo[@revision]` can be passed as a model id too,
+/// as long as the repository ships a KittenTTS-style `config.json`.
+///
+/// To pin an entry, set `revision` to the commit SHA reported by
+/// `https://huggingface.co/api/models/<repo>` (its `sha` field) and list the files' SHA-256
+/// (their LFS `oid`) in `sha256`. Entries still on `main` follow the branch.
+pub const REGISTRY: &[RegistryEntry] = &[
+    RegistryEntry {
+        id: "nano-0.1",
+        repo: "KittenML/kitten-tts-nano-0.1",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "Original 15M parameter preview model",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "nano-0.2",
+        repo: "KittenML/kitten-tts-nano-0.2",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "15M parameter model, improved prosody",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "mini-0.1",
+        repo: "KittenML/kitten-tts-mini-0.1",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "Larger preview model with higher quality",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "nano-0.8",
+        repo: "KittenML/kitten-tts-nano-0.8",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "15M parameter model",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "nano-0.8-int8",
+        repo: "KittenML/kitten-tts-nano-0.8-int8",
+        revision: "main",
+        precision: Precision::Int8,
+        description: "15M parameter model, int8 quantized",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "micro-0.8",
+        repo: "KittenML/kitten-tts-micro-0.8",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "40M parameter model",
+        sha256: &[],
+    },
+    RegistryEntry {
+        id: "mini-0.8",
+        repo: "KittenML/kitten-tts-mini-0.8",
+        revision: "main",
+        precision: Precision::Fp32,
+        description: "80M parameter model, highest quality",
+        sha256: &[],
+    },
+];
+
+/// Where a model comes from and where it lives on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSpec {
+    pub id: String,
+    pub repo: String,
+    pub revision: String,
+    /// Never download: fail with the list of missing files instead.
+    /// Defaults to the `KITTENX_OFFLINE` environment variable.
+    pub offline: bool,
+    /// Known SHA-256 of the model's files, from the registry (empty for other repositories)
+    pub sha256: &'static [(&'static str, &'static str)],
+}
+
+impl Default for ModelSpec {
+    fn default() -> Self {
+        Self::resolve(DEFAULT_MODEL_ID).expect("default model is registered")
+    }
+}
+
+impl ModelSpec {
+    /// Resolve a registry id (`nano-0.8`) or a HuggingFace repository (`owner/repo[@revision]`)
+    pub fn resolve(id: &str) -> Result<Self> {
+        let id = id.trim();
+        if let Some(entry) = REGISTRY.iter().find(|e| e.id.eq_ignore_ascii_case(id)) {
+            return Ok(Self {
+                id: entry.id.to_string(),
+                repo: entry.repo.to_string(),
+                revision: entry.revision.to_string(),
+                offline: offline_from_env(),
+                sha256: entry.sha256,
+            });
+        }
+
+        let (repo, revision) = match id.split_once('@') {
+            Some((repo, revision)) => (repo, revision),
+            None => (id, "main"),
+        };
+        let valid_repo = repo.split('/').count() == 2 && repo.split('/').all(|part| !part.is_empty());
+        if !valid_repo || revision.is_empty() {
+            let known: Vec<&str> = REGISTRY.iter().map(|e| e.id).collect();
+            anyhow::bail!(
+                "Unknown model '{}'. Use one of {:?} or a HuggingFace repository such as owner/repo[@revision]",
+                id,
+                known
+            );
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            repo: repo.to_string(),
+            revision: revision.to_string(),
+            offline: offline_from_env(),
+            sha256: &[],
+        })
+    }
+
+    /// Forbid (or allow) downloading missing files
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Whether this is one of the `REGISTRY` models, whose downloads must be verifiable
+    pub fn is_registered(&self) -> bool {
+        REGISTRY.iter().any(|entry| entry.id == self.id)
+    }
+
+    /// SHA-256 the registry ships for `file`, if any
+    pub fn known_sha256(&self, file: &str) -> Option<&'static str> {
+        self.sha256.iter().find(|(name, _)| *name == file).map(|(_, sha256)| *sha256)
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_MODEL_ID
+    }
+
+    /// Directory holding this model's files. The default model keeps using the
+    /// model directory root so existing downloads are picked up as before.
+    pub fn local_dir(&self, model_dir: &Path) -> PathBuf {
+        if self.is_default() {
+            model_dir.to_path_buf()
+        } else {
+            model_dir.join(self.id.replace(['/', '@'], "--"))
+        }
+    }
+
+    /// Download URL of a file in the model repository (on `HF_ENDPOINT` if set)
+    pub fn file_url(&self, file: &str) -> String {
+        format!("{}/{}/resolve/{}/{}", hub_endpoint(), self.repo, self.revision, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "registry entries still follow main; pin them to the commit reported by the Hub API"]
+    fn registry_revisions_are_commit_shas() {
+        for entry in REGISTRY {
+            let is_sha = entry.revision.len() == 40 && entry.revision.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'));
+            assert!(is_sha, "{} is not pinned to a commit SHA: {}", entry.id, entry.revision);
+        }
+    }
+}
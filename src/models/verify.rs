@@ -0,0 +1,117 @@
+use super::{ModelConfig, ModelFiles, ModelSpec};
+use crate::onnx::proto::ModelSummary;
+use crate::utils::download::{remote_sha256, sha256_file};
+use anyhow::Result;
+use ndarray_npy::NpzReader;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Result of checking one model file
+#[derive(Debug, Clone)]
+pub enum CheckStatus {
+    /// Parses correctly and matches the published SHA-256
+    Verified,
+    /// Parses correctly, but there is no published SHA-256 to compare against
+    Unverified(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct FileCheck {
+    pub path: PathBuf,
+    pub sha256: Option<String>,
+    pub status: CheckStatus,
+}
+
+impl FileCheck {
+    pub fn is_ok(&self) -> bool {
+        !matches!(self.status, CheckStatus::Failed(_))
+    }
+}
+
+impl fmt::Display for FileCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            CheckStatus::Verified => write!(f, "OK        {} (sha256 matches)", self.path.display()),
+            CheckStatus::Unverified(why) => write!(f, "OK        {} ({})", self.path.display(), why),
+            CheckStatus::Failed(why) => write!(f, "FAILED    {}: {}", self.path.display(), why),
+        }
+    }
+}
+
+/// Parses a file to make sure it is usable
+type StructureCheck = fn(&Path) -> Result<()>;
+
+/// Check that each model file parses and, where a reference hash is known, that its
+/// SHA-256 matches. Reference hashes come from the registry, the cache's blob names (HF
+/// stores LFS blobs under their SHA-256) or, unless the spec is offline, from the Hub itself.
+pub async fn verify_model(model: &ModelSpec, files: &ModelFiles, config: &ModelConfig) -> Vec<FileCheck> {
+    let mut entries: Vec<(&str, &Path, StructureCheck)> = vec![
+        (&config.model_file, &files.model, check_onnx),
+        (&config.voices, &files.voices, check_voices),
+    ];
+    if let Some(config_path) = &files.config {
+        entries.insert(0, ("config.json", config_path, check_config));
+    }
+
+    let mut checks = Vec::new();
+    for (remote_name, path, check_structure) in entries {
+        let sha256 = match sha256_file(path).await {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                checks.push(FileCheck { path: path.to_path_buf(), sha256: None, status: CheckStatus::Failed(e.to_string()) });
+                continue;
+            }
+        };
+
+        let status = if let Err(e) = check_structure(path) {
+            CheckStatus::Failed(format!("{:#}", e))
+        } else {
+            match reference_sha256(model, remote_name, path).await {
+                Ok(Some(expected)) if expected == sha256 => CheckStatus::Verified,
+                Ok(Some(expected)) => {
+                    CheckStatus::Failed(format!("SHA-256 mismatch: expected {}, got {}", expected, sha256))
+                }
+                Ok(None) if model.offline => CheckStatus::Unverified("offline, no cached reference hash".to_string()),
+                Ok(None) => CheckStatus::Unverified("no published SHA-256".to_string()),
+                Err(e) => CheckStatus::Unverified(format!("could not look up the published SHA-256: {:#}", e)),
+            }
+        };
+        checks.push(FileCheck { path: path.to_path_buf(), sha256: Some(sha256), status });
+    }
+    checks
+}
+
+async fn reference_sha256(model: &ModelSpec, remote_name: &str, path: &Path) -> Result<Option<String>> {
+    if let Some(sha256) = model.known_sha256(remote_name) {
+        return Ok(Some(sha256.to_ascii_lowercase()));
+    }
+    // snapshots/<commit>/<file> links to blobs/<etag>; LFS etags are SHA-256
+    if let Ok(blob) = std::fs::canonicalize(path) {
+        let blob_name = blob.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if blob != path && blob_name.len() == 64 && blob_name.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Some(blob_name.to_ascii_lowercase()));
+        }
+    }
+    if model.offline {
+        return Ok(None);
+    }
+    remote_sha256(&model.file_url(remote_name)).await
+}
+
+fn check_config(path: &Path) -> Result<()> {
+    ModelConfig::load(path).map(|_| ())
+}
+
+fn check_onnx(path: &Path) -> Result<()> {
+    let summary = ModelSummary::load(path)?;
+    anyhow::ensure!(!summary.inputs.is_empty() && !summary.outputs.is_empty(), "Model graph has no inputs or outputs");
+    Ok(())
+}
+
+fn check_voices(path: &Path) -> Result<()> {
+    let mut npz = NpzReader::new(File::open(path)?)?;
+    anyhow::ensure!(!npz.names()?.is_empty(), "Voices file contains no voices");
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// Inference runtimes behind a common interface
+// `KittenTTS` only talks to `InferenceBackend`, so the runtime is picked at load time
+// (`SessionOptions::backend`) and each one is compiled in by its own cargo feature.
+
+use super::{AccelerationProvider, BatchItem, ModelOutputs, ModelSignature, ProviderStatus, SessionOptions};
+use anyhow::Result;
+use ndarray::{ArrayBase, IxDyn, OwnedRepr};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A runtime that executes a KittenTTS ONNX model
+pub trait InferenceBackend: Send + Sync {
+    /// Input/output layout of the loaded model
+    fn signature(&self) -> &ModelSignature;
+
+    /// Which execution provider was requested and which one runs the model
+    fn provider_status(&self) -> &ProviderStatus;
+
+    /// Run one utterance, returning the waveform, durations and any other outputs
+    fn infer_outputs(&self, input_ids: Vec<Vec<i64>>, style: Vec<f32>, speed: f32) -> Result<ModelOutputs>;
+
+    fn infer(
+        &self,
+        input_ids: Vec<Vec<i64>>,
+        style: Vec<f32>,
+        speed: f32,
+    ) -> Result<ArrayBase<OwnedRepr<f32>, IxDyn>> {
+        Ok(self.infer_outputs(input_ids, style, speed)?.waveform)
+    }
+
+    /// Run several utterances, returning one waveform per item. Backends without
+    /// batching run the items one at a time.
+    fn infer_batch(&self, items: &[BatchItem]) -> Result<Vec<Vec<f32>>> {
+        items
+            .iter()
+            .map(|item| {
+                let outputs = self.infer_outputs(vec![item.input_ids.clone()], item.style.clone(), item.speed)?;
+                Ok(outputs.waveform.into_iter().collect())
+            })
+            .collect()
+    }
+}
+
+/// Runtime used to execute the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Backend {
+    /// ONNX Runtime, with hardware acceleration (the `onnxruntime` feature)
+    #[cfg_attr(feature = "cli", value(name = "onnxruntime", alias = "ort"))]
+    OnnxRuntime,
+    /// tract, pure Rust and CPU only (the `tract` feature)
+    Tract,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(feature = "onnxruntime") || !cfg!(feature = "tract") {
+            Backend::OnnxRuntime
+        } else {
+            Backend::Tract
+        }
+    }
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::OnnxRuntime => "onnxruntime",
+            Backend::Tract => "tract",
+        }
+    }
+
+    /// Load the model at `model_path` with this runtime, failing if it was not compiled in
+    // Builds without one of the backends don't use every argument
+    #[allow(unused_variables)]
+    pub fn load(
+        &self,
+        model_path: &Path,
+        provider: AccelerationProvider,
+        options: &SessionOptions,
+    ) -> Result<Arc<dyn InferenceBackend>> {
+        match self {
+            #[cfg(feature = "onnxruntime")]
+            Backend::OnnxRuntime => {
+                let model_path = model_path
+                    .to_str()
+                    .ok_or_else(|| anyhow::anyhow!("Model path is not valid UTF-8: {}", model_path.display()))?;
+                Ok(Arc::new(super::KittenOnnx::with_options(model_path, provider, options)?))
+            }
+            #[cfg(feature = "tract")]
+            Backend::Tract => Ok(Arc::new(super::TractOnnx::from_file(model_path)?.with_requested_provider(provider))),
+            #[allow(unreachable_patterns)]
+            other => anyhow::bail!(
+                "Inference backend {} is not available: kittenx was built without the `{}` feature",
+                other,
+                other.name()
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -1,127 +1,202 @@
-use std::borrow::Cow;
-use ndarray::{ArrayBase, IxDyn, OwnedRepr};
-use ort::{
-    session::{Session, SessionInputValue, SessionInputs, SessionOutputs, builder::SessionBuilder},
-    value::{Tensor, Value},
-};
-
-#[cfg(feature = "cuda")]
-use ort::execution_providers::cuda::CUDAExecutionProvider;
-#[cfg(feature = "coreml")]
-use ort::execution_providers::coreml::CoreMLExecutionProvider;
-use ort::execution_providers::cpu::CPUExecutionProvider;
+mod backend;
+pub mod proto;
+pub mod quantize;
+#[cfg(feature = "onnxruntime")]
+mod runtime;
+pub mod signature;
+#[cfg(feature = "tract")]
+mod tract;
+
+pub use backend::{Backend, InferenceBackend};
+pub use quantize::{quantize_file, QuantizeOptions, QuantizeReport};
+#[cfg(feature = "onnxruntime")]
+pub use runtime::KittenOnnx;
+pub use signature::{ElementType, ModelSignature, TensorSpec};
+#[cfg(feature = "tract")]
+pub use tract::TractOnnx;
+
+use crate::tts::LogLevel;
 use anyhow::Result;
+use ndarray::{ArrayBase, IxDyn, OwnedRepr};
+use std::path::PathBuf;
+
+/// Everything one inference run produced
+#[derive(Debug, Clone)]
+pub struct ModelOutputs {
+    pub waveform: ArrayBase<OwnedRepr<f32>, IxDyn>,
+    /// Predicted duration of each input token, in frames
+    pub durations: Option<Vec<f32>>,
+    /// Other outputs the model exports, by name
+    pub extra: Vec<(String, ArrayBase<OwnedRepr<f32>, IxDyn>)>,
+}
 
-pub struct KittenOnnx {
-    session: Option<Session>,
+/// One utterance in a batched inference call
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    pub input_ids: Vec<i64>,
+    pub style: Vec<f32>,
+    pub speed: f32,
 }
 
-#[derive(Debug, Clone, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum AccelerationProvider {
     Cpu,
     Cuda,
+    #[cfg_attr(feature = "cli", value(name = "coreml"))]
     CoreML,
+    #[cfg_attr(feature = "cli", value(name = "directml"))]
     DirectML,
+    #[cfg_attr(feature = "cli", value(name = "tensorrt"))]
     TensorRT,
+    #[cfg_attr(feature = "cli", value(name = "rocm"))]
     ROCm,
+    #[cfg_attr(feature = "cli", value(name = "openvino"))]
     OpenVINO,
+    #[cfg_attr(feature = "cli", value(name = "onednn"))]
     OneDNN,
+    #[cfg_attr(feature = "cli", value(name = "webgpu"))]
     WebGPU,
 }
 
-unsafe impl Send for KittenOnnx {}
-unsafe impl Sync for KittenOnnx {}
+impl AccelerationProvider {
+    /// Every provider, whether compiled in or not
+    pub const ALL: [AccelerationProvider; 9] = [
+        AccelerationProvider::Cpu,
+        AccelerationProvider::Cuda,
+        AccelerationProvider::CoreML,
+        AccelerationProvider::DirectML,
+        AccelerationProvider::TensorRT,
+        AccelerationProvider::ROCm,
+        AccelerationProvider::OpenVINO,
+        AccelerationProvider::OneDNN,
+        AccelerationProvider::WebGPU,
+    ];
+
+    /// CLI name of the provider, which is also the name of its cargo feature
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccelerationProvider::Cpu => "cpu",
+            AccelerationProvider::Cuda => "cuda",
+            AccelerationProvider::CoreML => "coreml",
+            AccelerationProvider::DirectML => "directml",
+            AccelerationProvider::TensorRT => "tensorrt",
+            AccelerationProvider::ROCm => "rocm",
+            AccelerationProvider::OpenVINO => "openvino",
+            AccelerationProvider::OneDNN => "onednn",
+            AccelerationProvider::WebGPU => "webgpu",
+        }
+    }
 
-impl KittenOnnx {
-    pub fn new(model_path: &str) -> Result<Self> {
-        Self::with_provider(model_path, AccelerationProvider::Cpu)
+    /// Whether support for this provider was compiled into this build
+    pub fn is_compiled_in(&self) -> bool {
+        match self {
+            AccelerationProvider::Cpu => true,
+            AccelerationProvider::Cuda => cfg!(feature = "cuda"),
+            AccelerationProvider::CoreML => cfg!(feature = "coreml"),
+            AccelerationProvider::DirectML => cfg!(feature = "directml"),
+            AccelerationProvider::TensorRT => cfg!(feature = "tensorrt"),
+            AccelerationProvider::ROCm => cfg!(feature = "rocm"),
+            AccelerationProvider::OpenVINO => cfg!(feature = "openvino"),
+            AccelerationProvider::OneDNN => cfg!(feature = "onednn"),
+            AccelerationProvider::WebGPU => cfg!(feature = "webgpu"),
+        }
     }
+}
 
-    pub fn with_provider(model_path: &str, provider: AccelerationProvider) -> Result<Self> {
-        let mut instance = KittenOnnx { session: None };
-        instance.load_model_with_provider(model_path, provider)?;
-        Ok(instance)
+impl std::fmt::Display for AccelerationProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
     }
+}
+
+impl std::str::FromStr for AccelerationProvider {
+    type Err = anyhow::Error;
 
-    #[allow(dead_code)]
-    fn load_model(&mut self, model_path: &str) -> Result<()> {
-        self.load_model_with_provider(model_path, AccelerationProvider::Cpu)
+    /// Parse a provider name as accepted by `--provider`, ignoring case
+    fn from_str(name: &str) -> Result<Self> {
+        Self::ALL.into_iter().find(|provider| provider.name().eq_ignore_ascii_case(name.trim())).ok_or_else(|| {
+            let known: Vec<&str> = Self::ALL.iter().map(Self::name).collect();
+            anyhow::anyhow!("Unknown provider '{}'. Known providers: {}", name, known.join(", "))
+        })
     }
+}
+
+/// Per-provider settings. Options a provider doesn't understand are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderOptions {
+    /// GPU device index (CUDA, TensorRT, DirectML, ROCm, WebGPU)
+    pub device_id: Option<i32>,
+    /// Directory for cached TensorRT engines; enables the engine cache when set
+    pub tensorrt_engine_cache_path: Option<PathBuf>,
+    /// Allow half-precision kernels (TensorRT, OpenVINO)
+    pub fp16: bool,
+    /// OpenVINO device type, e.g. `CPU`, `GPU` or `NPU`
+    pub openvino_device_type: Option<String>,
+}
+
+/// Which execution provider was requested and which one the session actually runs on
+#[derive(Debug, Clone)]
+pub struct ProviderStatus {
+    pub requested: AccelerationProvider,
+    pub active: AccelerationProvider,
+    /// Why the requested provider could not be registered, if it fell back to CPU
+    pub fallback_reason: Option<String>,
+}
 
-    fn load_model_with_provider(&mut self, model_path: &str, provider: AccelerationProvider) -> Result<()> {
-        let builder = SessionBuilder::new()?;
-
-        // Configure execution providers based on the selected provider
-        let builder = match provider {
-            AccelerationProvider::Cpu => {
-                println!("Using CPU execution provider");
-                builder.with_execution_providers([CPUExecutionProvider::default().build()])?
-            }
-            #[cfg(feature = "cuda")]
-            AccelerationProvider::Cuda => {
-                println!("Using CUDA execution provider");
-                builder.with_execution_providers([
-                    CUDAExecutionProvider::default().build(),
-                    CPUExecutionProvider::default().build(),
-                ])?
-            }
-            #[cfg(feature = "coreml")]
-            AccelerationProvider::CoreML => {
-                println!("Using CoreML execution provider");
-                builder.with_execution_providers([
-                    CoreMLExecutionProvider::default().build(),
-                    CPUExecutionProvider::default().build(),
-                ])?
-            }
-            _ => {
-                println!("Requested provider not available in this build, falling back to CPU");
-                builder.with_execution_providers([CPUExecutionProvider::default().build()])?
-            }
-        };
-
-        let session = builder.commit_from_file(model_path)?;
-        self.session = Some(session);
-        Ok(())
+impl ProviderStatus {
+    pub fn is_fallback(&self) -> bool {
+        self.requested != self.active
     }
+}
 
-    pub fn infer(
-        &mut self,
-        input_ids: Vec<Vec<i64>>,
-        style: Vec<f32>,
-        speed: f32,
-    ) -> Result<ArrayBase<OwnedRepr<f32>, IxDyn>> {
-        let session = self.session.as_mut()
-            .ok_or_else(|| anyhow::anyhow!("Session not initialized"))?;
-
-        // Prepare input_ids tensor
-        let shape = [input_ids.len(), input_ids[0].len()];
-        let input_ids_flat: Vec<i64> = input_ids.into_iter().flatten().collect();
-        let input_ids_tensor = Tensor::from_array((shape, input_ids_flat))?;
-        let input_ids_value = SessionInputValue::Owned(Value::from(input_ids_tensor));
-
-        // Prepare style tensor
-        let style_shape = [1, style.len()];
-        let style_tensor = Tensor::from_array((style_shape, style))?;
-        let style_value = SessionInputValue::Owned(Value::from(style_tensor));
-
-        // Prepare speed tensor
-        let speed_tensor = Tensor::from_array(([1], vec![speed]))?;
-        let speed_value = SessionInputValue::Owned(Value::from(speed_tensor));
-
-        let inputs: Vec<(Cow<str>, SessionInputValue)> = vec![
-            (Cow::Borrowed("input_ids"), input_ids_value),
-            (Cow::Borrowed("style"), style_value),
-            (Cow::Borrowed("speed"), speed_value),
-        ];
-
-        let outputs: SessionOutputs = session.run(SessionInputs::from(inputs))?;
-        let output_tensor = outputs[0].try_extract_tensor::<f32>()?;
-        
-        // Convert the tensor data to ndarray
-        let (shape, data) = output_tensor;
-        let dims: Vec<usize> = shape.iter().map(|&x| x as usize).collect();
-        let output = ArrayBase::from_shape_vec(IxDyn(&dims), data.to_vec())?;
-
-        Ok(output)
+impl std::fmt::Display for ProviderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.fallback_reason {
+            Some(reason) => write!(f, "{} (requested {}: {})", self.active, self.requested, reason),
+            None => write!(f, "{}", self.active),
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Graph optimization level applied when the session is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OptimizationLevel {
+    /// No graph optimizations
+    Disable,
+    /// Constant folding and redundant node elimination
+    Basic,
+    /// Basic plus complex node fusions
+    Extended,
+    /// Everything, including memory layout optimizations (ONNX Runtime default)
+    #[default]
+    All,
+}
+
+/// ONNX Runtime session tuning. `None` fields keep ONNX Runtime's defaults.
+#[derive(Debug, Clone, Default)]
+pub struct SessionOptions {
+    /// Threads used to parallelize work within an operator
+    pub intra_threads: Option<usize>,
+    /// Threads used to run independent operators when `parallel_execution` is on
+    pub inter_threads: Option<usize>,
+    pub optimization_level: Option<OptimizationLevel>,
+    /// Memory pattern planning; disable it if input sizes vary a lot
+    pub memory_pattern: Option<bool>,
+    pub provider_options: ProviderOptions,
+    /// Use deterministic kernels, trading some speed for reproducible output
+    pub deterministic_compute: bool,
+    /// Run independent graph branches in parallel instead of sequentially
+    pub parallel_execution: bool,
+    /// Number of sessions to load for concurrent inference (default 1). Each
+    /// session holds its own copy of the model, and each uses `intra_threads`
+    /// threads, so size both together to avoid oversubscribing cores.
+    pub pool_size: Option<usize>,
+    /// Runtime that executes the model (default: ONNX Runtime when compiled in, else tract).
+    /// The other options only apply to ONNX Runtime.
+    pub backend: Option<Backend>,
+    /// What the runtime reports on stderr (provider, session pool, fallbacks). `KittenTTS`
+    /// sets it from the builder's log level.
+    pub log_level: LogLevel,
+}
@@ -0,0 +1,341 @@
+// Minimal reader and writer for the ONNX protobuf format
+// Only decodes what kittenx needs to describe a model without creating a session:
+// opsets, producer, graph inputs/outputs and initializer statistics. The writer
+// re-encodes fields verbatim so `quantize` can rewrite a graph without a full schema.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// A decoded protobuf field value
+pub enum Field<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> Field<'a> {
+    pub(crate) fn varint(&self) -> Option<u64> {
+        match self {
+            Field::Varint(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Field::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn string(&self) -> String {
+        self.bytes().map(|b| String::from_utf8_lossy(b).into_owned()).unwrap_or_default()
+    }
+}
+
+/// Iterates over the `(field number, value)` pairs of one protobuf message
+pub struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.buf.split_first().context("Truncated varint")?;
+            self.buf = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        anyhow::bail!("Varint is too long")
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        anyhow::ensure!(self.buf.len() >= len, "Truncated field");
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    pub fn next_field(&mut self) -> Result<Option<(u32, Field<'a>)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        let key = self.read_varint()?;
+        let number = (key >> 3) as u32;
+        let field = match key & 7 {
+            0 => Field::Varint(self.read_varint()?),
+            1 => Field::Fixed64(u64::from_le_bytes(self.take(8)?.try_into()?)),
+            2 => {
+                let len = self.read_varint()? as usize;
+                Field::Bytes(self.take(len)?)
+            }
+            5 => Field::Fixed32(u32::from_le_bytes(self.take(4)?.try_into()?)),
+            wire => anyhow::bail!("Unsupported protobuf wire type {}", wire),
+        };
+        Ok(Some((number, field)))
+    }
+}
+
+/// Append a base-128 varint
+pub fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub fn write_varint_field(out: &mut Vec<u8>, number: u32, value: u64) {
+    write_varint(out, u64::from(number) << 3);
+    write_varint(out, value);
+}
+
+pub fn write_bytes_field(out: &mut Vec<u8>, number: u32, bytes: &[u8]) {
+    write_varint(out, (u64::from(number) << 3) | 2);
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// Re-encode a field exactly as it was read
+pub fn write_field(out: &mut Vec<u8>, number: u32, field: &Field) {
+    match field {
+        Field::Varint(v) => write_varint_field(out, number, *v),
+        Field::Fixed64(v) => {
+            write_varint(out, (u64::from(number) << 3) | 1);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Field::Bytes(b) => write_bytes_field(out, number, b),
+        Field::Fixed32(v) => {
+            write_varint(out, (u64::from(number) << 3) | 5);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+/// Read a repeated int64 field that may be packed or not
+pub(crate) fn push_ints(field: &Field, out: &mut Vec<i64>) -> Result<()> {
+    match field {
+        Field::Varint(v) => out.push(*v as i64),
+        Field::Bytes(packed) => {
+            let mut reader = Reader::new(packed);
+            while !reader.buf.is_empty() {
+                out.push(reader.read_varint()? as i64);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// ONNX `TensorProto.DataType` name
+pub fn elem_type_name(elem_type: i32) -> &'static str {
+    match elem_type {
+        1 => "float32",
+        2 => "uint8",
+        3 => "int8",
+        4 => "uint16",
+        5 => "int16",
+        6 => "int32",
+        7 => "int64",
+        8 => "string",
+        9 => "bool",
+        10 => "float16",
+        11 => "float64",
+        12 => "uint32",
+        13 => "uint64",
+        16 => "bfloat16",
+        _ => "unknown",
+    }
+}
+
+/// One dimension of a tensor shape
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dim {
+    Fixed(i64),
+    Symbolic(String),
+    Unknown,
+}
+
+/// A graph input or output
+#[derive(Debug, Clone)]
+pub struct TensorInfo {
+    pub name: String,
+    pub elem_type: i32,
+    pub dims: Vec<Dim>,
+}
+
+impl fmt::Display for TensorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dims: Vec<String> = self
+            .dims
+            .iter()
+            .map(|d| match d {
+                Dim::Fixed(n) => n.to_string(),
+                Dim::Symbolic(s) => s.clone(),
+                Dim::Unknown => "?".to_string(),
+            })
+            .collect();
+        write!(f, "{}: {}[{}]", self.name, elem_type_name(self.elem_type), dims.join(", "))
+    }
+}
+
+/// What `ModelSummary::load` extracts from an `.onnx` file
+#[derive(Debug, Clone, Default)]
+pub struct ModelSummary {
+    pub ir_version: i64,
+    pub producer_name: String,
+    pub producer_version: String,
+    /// `(domain, version)`; the default domain is reported as `ai.onnx`
+    pub opsets: Vec<(String, i64)>,
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+    pub initializer_count: usize,
+    pub parameter_count: u64,
+    /// Number of initializer elements per element type
+    pub parameters_by_type: Vec<(i32, u64)>,
+}
+
+impl ModelSummary {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&bytes).with_context(|| format!("{} is not a valid ONNX model", path.display()))
+    }
+
+    pub fn parse(model: &[u8]) -> Result<Self> {
+        let mut summary = ModelSummary::default();
+        let mut graph = None;
+
+        let mut reader = Reader::new(model);
+        while let Some((number, field)) = reader.next_field()? {
+            match number {
+                1 => summary.ir_version = field.varint().unwrap_or_default() as i64,
+                2 => summary.producer_name = field.string(),
+                3 => summary.producer_version = field.string(),
+                7 => graph = field.bytes(),
+                8 => {
+                    let mut domain = String::new();
+                    let mut version = 0;
+                    let mut opset = Reader::new(field.bytes().unwrap_or_default());
+                    while let Some((number, field)) = opset.next_field()? {
+                        match number {
+                            1 => domain = field.string(),
+                            2 => version = field.varint().unwrap_or_default() as i64,
+                            _ => {}
+                        }
+                    }
+                    if domain.is_empty() {
+                        domain = "ai.onnx".to_string();
+                    }
+                    summary.opsets.push((domain, version));
+                }
+                _ => {}
+            }
+        }
+
+        let graph = graph.context("Model has no graph")?;
+        let mut initializer_names = HashSet::new();
+        let mut inputs = Vec::new();
+        let mut reader = Reader::new(graph);
+        while let Some((number, field)) = reader.next_field()? {
+            let Some(bytes) = field.bytes() else { continue };
+            match number {
+                5 => {
+                    let (name, elem_type, elements) = parse_initializer(bytes)?;
+                    initializer_names.insert(name);
+                    summary.initializer_count += 1;
+                    summary.parameter_count += elements;
+                    match summary.parameters_by_type.iter_mut().find(|(t, _)| *t == elem_type) {
+                        Some((_, count)) => *count += elements,
+                        None => summary.parameters_by_type.push((elem_type, elements)),
+                    }
+                }
+                11 => inputs.push(parse_value_info(bytes)?),
+                12 => summary.outputs.push(parse_value_info(bytes)?),
+                _ => {}
+            }
+        }
+        // Older IR versions also list initializers as graph inputs
+        summary.inputs = inputs.into_iter().filter(|i| !initializer_names.contains(&i.name)).collect();
+
+        Ok(summary)
+    }
+
+    /// Opset version of the default `ai.onnx` domain
+    pub fn default_opset(&self) -> Option<i64> {
+        self.opsets.iter().find(|(domain, _)| domain == "ai.onnx").map(|(_, v)| *v)
+    }
+}
+
+/// `(name, elem_type, element count)` of a `TensorProto`
+fn parse_initializer(tensor: &[u8]) -> Result<(String, i32, u64)> {
+    let mut dims = Vec::new();
+    let mut elem_type = 0;
+    let mut name = String::new();
+    let mut reader = Reader::new(tensor);
+    while let Some((number, field)) = reader.next_field()? {
+        match number {
+            1 => push_ints(&field, &mut dims)?,
+            2 => elem_type = field.varint().unwrap_or_default() as i32,
+            8 => name = field.string(),
+            _ => {}
+        }
+    }
+    let elements = dims.iter().map(|&d| d.max(0) as u64).product();
+    Ok((name, elem_type, elements))
+}
+
+fn parse_value_info(value_info: &[u8]) -> Result<TensorInfo> {
+    let mut info = TensorInfo { name: String::new(), elem_type: 0, dims: Vec::new() };
+    let mut reader = Reader::new(value_info);
+    while let Some((number, field)) = reader.next_field()? {
+        match (number, field.bytes()) {
+            (1, _) => info.name = field.string(),
+            (2, Some(type_proto)) => {
+                // TypeProto.tensor_type -> elem_type, shape.dim[]
+                let mut types = Reader::new(type_proto);
+                while let Some((number, field)) = types.next_field()? {
+                    let (1, Some(tensor_type)) = (number, field.bytes()) else { continue };
+                    let mut tensor = Reader::new(tensor_type);
+                    while let Some((number, field)) = tensor.next_field()? {
+                        match (number, field.bytes()) {
+                            (1, _) => info.elem_type = field.varint().unwrap_or_default() as i32,
+                            (2, Some(shape)) => info.dims = parse_shape(shape)?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+fn parse_shape(shape: &[u8]) -> Result<Vec<Dim>> {
+    let mut dims = Vec::new();
+    let mut reader = Reader::new(shape);
+    while let Some((number, field)) = reader.next_field()? {
+        let (1, Some(dim_proto)) = (number, field.bytes()) else { continue };
+        let mut dim = Dim::Unknown;
+        let mut dim_reader = Reader::new(dim_proto);
+        while let Some((number, field)) = dim_reader.next_field()? {
+            match number {
+                1 => dim = Dim::Fixed(field.varint().unwrap_or_default() as i64),
+                2 => dim = Dim::Symbolic(field.string()),
+                _ => {}
+            }
+        }
+        dims.push(dim);
+    }
+    Ok(dims)
+}
@@ -0,0 +1,420 @@
+// Dynamic int8 quantization of fp32 models, done directly on the protobuf
+// MatMul weights become int8 and run through MatMulInteger after the activations are
+// quantized at run time (DynamicQuantizeLinear), like onnxruntime's `quantize_dynamic`.
+// Other large weights (Conv, LSTM, embeddings) are stored as int8 and dequantized when
+// the session is created, which shrinks the file without touching those kernels.
+
+use super::proto::{push_ints, write_bytes_field, write_field, write_varint_field, Field, ModelSummary, Reader};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// ONNX `TensorProto.DataType` values used here
+const FLOAT: u64 = 1;
+const INT8: u64 = 3;
+
+/// DequantizeLinear needs opset 10, DynamicQuantizeLinear opset 11
+const MIN_OPSET: i64 = 10;
+const DYNAMIC_OPSET: i64 = 11;
+
+#[derive(Debug, Clone)]
+pub struct QuantizeOptions {
+    /// Weights with fewer elements (biases, norms, small projections) stay fp32
+    pub min_elements: usize,
+    /// Run MatMuls on int8 kernels; when false their weights are dequantized at load
+    /// time like all other weights, trading speed for accuracy
+    pub integer_matmul: bool,
+}
+
+impl Default for QuantizeOptions {
+    fn default() -> Self {
+        Self { min_elements: 1024, integer_matmul: true }
+    }
+}
+
+/// What `quantize_model` changed
+#[derive(Debug, Clone, Default)]
+pub struct QuantizeReport {
+    /// MatMul nodes rewritten to MatMulInteger
+    pub integer_matmuls: usize,
+    /// Weights stored as int8 and dequantized at load time
+    pub dequantized_weights: usize,
+    /// Float weights left as they were
+    pub fp32_weights: usize,
+    /// Number of weight elements now stored as int8
+    pub quantized_parameters: u64,
+    pub input_bytes: usize,
+    pub output_bytes: usize,
+}
+
+/// Quantize `input` and write the result to `output`
+pub fn quantize_file(input: &Path, output: &Path, options: &QuantizeOptions) -> Result<QuantizeReport> {
+    let model = std::fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?;
+    let (quantized, report) =
+        quantize_model(&model, options).with_context(|| format!("Failed to quantize {}", input.display()))?;
+    std::fs::write(output, quantized).with_context(|| format!("Failed to write {}", output.display()))?;
+    Ok(report)
+}
+
+/// Quantize the fp32 weights of a serialized `ModelProto`
+pub fn quantize_model(model: &[u8], options: &QuantizeOptions) -> Result<(Vec<u8>, QuantizeReport)> {
+    let summary = ModelSummary::parse(model)?;
+    let opset = summary.default_opset().unwrap_or(0);
+    anyhow::ensure!(
+        opset >= MIN_OPSET,
+        "Model uses opset {} but quantization needs at least opset {}; re-export the model with a newer opset",
+        opset,
+        MIN_OPSET
+    );
+    let integer_matmul = options.integer_matmul && opset >= DYNAMIC_OPSET;
+
+    let mut report = QuantizeReport { input_bytes: model.len(), ..Default::default() };
+    let mut out = Vec::with_capacity(model.len() / 3);
+    let mut reader = Reader::new(model);
+    while let Some((number, field)) = reader.next_field()? {
+        match (number, field.bytes()) {
+            (7, Some(graph)) => write_bytes_field(&mut out, 7, &quantize_graph(graph, options, integer_matmul, &mut report)?),
+            _ => write_field(&mut out, number, &field),
+        }
+    }
+    report.output_bytes = out.len();
+    Ok((out, report))
+}
+
+/// The parts of a `NodeProto` the rewrite looks at
+struct Node<'a> {
+    raw: &'a [u8],
+    op_type: String,
+    domain: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+/// A float32 initializer with its values decoded
+struct Weight {
+    name: String,
+    dims: Vec<i64>,
+    data: Vec<f32>,
+}
+
+/// How a weight is stored after quantization
+enum Plan {
+    /// Only consumed as the B input of MatMul nodes, which become MatMulInteger
+    IntegerMatMul,
+    /// Dequantized by a DequantizeLinear node producing the original name
+    Dequantize,
+}
+
+fn quantize_graph(graph: &[u8], options: &QuantizeOptions, integer_matmul: bool, report: &mut QuantizeReport) -> Result<Vec<u8>> {
+    let mut fields = Vec::new();
+    let mut nodes = Vec::new();
+    let mut weights = HashMap::new();
+    let mut graph_io = HashSet::new();
+    let mut subgraph_refs = HashSet::new();
+
+    let mut reader = Reader::new(graph);
+    while let Some((number, field)) = reader.next_field()? {
+        match (number, field.bytes()) {
+            (1, Some(raw)) => {
+                collect_subgraph_inputs(raw, &mut subgraph_refs)?;
+                nodes.push(parse_node(raw)?);
+            }
+            (5, Some(raw)) => {
+                if let Some(weight) = parse_float_initializer(raw)? {
+                    weights.insert(weight.name.clone(), weight);
+                }
+            }
+            // Older IR versions list initializers as inputs; those must stay overridable
+            (11 | 12, Some(raw)) => {
+                graph_io.insert(value_info_name(raw)?);
+            }
+            _ => {}
+        }
+        fields.push((number, field));
+    }
+
+    let mut uses: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        for (position, input) in node.inputs.iter().enumerate() {
+            uses.entry(input.as_str()).or_default().push((index, position));
+        }
+    }
+
+    // Ordered so the output is reproducible
+    let mut plans: BTreeMap<&str, Plan> = BTreeMap::new();
+    for weight in weights.values() {
+        if weight.data.len() < options.min_elements || graph_io.contains(&weight.name) {
+            report.fp32_weights += 1;
+            continue;
+        }
+        let only_matmul_b = uses.get(weight.name.as_str()).is_some_and(|uses| {
+            uses.iter().all(|&(node, position)| {
+                let node = &nodes[node];
+                node.op_type == "MatMul" && node.domain.is_empty() && position == 1 && node.inputs.len() == 2
+            })
+        });
+        let plan = if integer_matmul && only_matmul_b && weight.dims.len() == 2 && !subgraph_refs.contains(&weight.name) {
+            Plan::IntegerMatMul
+        } else {
+            report.dequantized_weights += 1;
+            Plan::Dequantize
+        };
+        report.quantized_parameters += weight.data.len() as u64;
+        plans.insert(weight.name.as_str(), plan);
+    }
+
+    let mut out = Vec::with_capacity(graph.len() / 3);
+    let mut node_index = 0;
+    for (number, field) in &fields {
+        match (*number, field.bytes()) {
+            (1, Some(_)) => {
+                if node_index == 0 {
+                    // Dequantized weights must exist before any node reads them
+                    for (name, plan) in &plans {
+                        if let Plan::Dequantize = plan {
+                            let node = encode_node(
+                                &format!("{}_DequantizeLinear", name),
+                                "DequantizeLinear",
+                                &[&quantized_name(name), &scale_name(name), &zero_point_name(name)],
+                                &[name],
+                                &[],
+                            );
+                            write_bytes_field(&mut out, 1, &node);
+                        }
+                    }
+                }
+                let node = &nodes[node_index];
+                node_index += 1;
+                match node.inputs.get(1).and_then(|b| plans.get(b.as_str())) {
+                    Some(Plan::IntegerMatMul) => {
+                        for rewritten in integer_matmul_nodes(node) {
+                            write_bytes_field(&mut out, 1, &rewritten);
+                        }
+                        report.integer_matmuls += 1;
+                    }
+                    _ => write_bytes_field(&mut out, 1, node.raw),
+                }
+            }
+            (5, Some(raw)) => {
+                let weight = parse_float_initializer(raw)?.filter(|w| plans.contains_key(w.name.as_str()));
+                match weight {
+                    Some(weight) => {
+                        let (quantized, scale) = quantize_symmetric(&weight.data);
+                        write_bytes_field(&mut out, 5, &encode_tensor(&quantized_name(&weight.name), INT8, &weight.dims, &quantized));
+                        write_bytes_field(&mut out, 5, &encode_tensor(&scale_name(&weight.name), FLOAT, &[], &scale.to_le_bytes()));
+                        write_bytes_field(&mut out, 5, &encode_tensor(&zero_point_name(&weight.name), INT8, &[], &[0]));
+                    }
+                    None => write_bytes_field(&mut out, 5, raw),
+                }
+            }
+            _ => write_field(&mut out, *number, field),
+        }
+    }
+    Ok(out)
+}
+
+fn quantized_name(weight: &str) -> String {
+    format!("{}_quantized", weight)
+}
+
+fn scale_name(weight: &str) -> String {
+    format!("{}_scale", weight)
+}
+
+fn zero_point_name(weight: &str) -> String {
+    format!("{}_zero_point", weight)
+}
+
+/// `Y = MatMul(A, W)` as DynamicQuantizeLinear + MatMulInteger, rescaled back to float
+fn integer_matmul_nodes(node: &Node) -> Vec<Vec<u8>> {
+    let (a, weight, y) = (&node.inputs[0], &node.inputs[1], &node.outputs[0]);
+    let prefix = format!("{}_dynamic", y);
+    let a_quantized = format!("{}_a_quantized", prefix);
+    let a_scale = format!("{}_a_scale", prefix);
+    let a_zero_point = format!("{}_a_zero_point", prefix);
+    let product = format!("{}_int32", prefix);
+    let product_float = format!("{}_float", prefix);
+    let scale = format!("{}_scale", prefix);
+
+    vec![
+        encode_node(
+            &format!("{}_DynamicQuantizeLinear", prefix),
+            "DynamicQuantizeLinear",
+            &[a],
+            &[&a_quantized, &a_scale, &a_zero_point],
+            &[],
+        ),
+        encode_node(
+            &format!("{}_MatMulInteger", prefix),
+            "MatMulInteger",
+            &[&a_quantized, &quantized_name(weight), &a_zero_point, &zero_point_name(weight)],
+            &[&product],
+            &[],
+        ),
+        encode_node(&format!("{}_Cast", prefix), "Cast", &[&product], &[&product_float], &[("to", FLOAT as i64)]),
+        encode_node(&format!("{}_ScaleMul", prefix), "Mul", &[&a_scale, &scale_name(weight)], &[&scale], &[]),
+        encode_node(&format!("{}_OutputMul", prefix), "Mul", &[&product_float, &scale], &[y], &[]),
+    ]
+}
+
+/// Per-tensor symmetric int8 quantization, returning the int8 bytes and the scale
+fn quantize_symmetric(data: &[f32]) -> (Vec<u8>, f32) {
+    let max = data.iter().fold(0.0f32, |max, v| if v.is_finite() { max.max(v.abs()) } else { max });
+    let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+    let quantized = data
+        .iter()
+        .map(|&v| (v / scale).round().clamp(-127.0, 127.0) as i8 as u8)
+        .collect();
+    (quantized, scale)
+}
+
+fn parse_node(raw: &[u8]) -> Result<Node<'_>> {
+    let mut node = Node { raw, op_type: String::new(), domain: String::new(), inputs: Vec::new(), outputs: Vec::new() };
+    let mut reader = Reader::new(raw);
+    while let Some((number, field)) = reader.next_field()? {
+        match number {
+            1 => node.inputs.push(field.string()),
+            2 => node.outputs.push(field.string()),
+            4 => node.op_type = field.string(),
+            7 => node.domain = field.string(),
+            _ => {}
+        }
+    }
+    // "ai.onnx" and "" are the same domain
+    if node.domain == "ai.onnx" {
+        node.domain.clear();
+    }
+    Ok(node)
+}
+
+/// Names read by nodes inside If/Loop/Scan bodies of `node`, which can see outer-scope tensors
+fn collect_subgraph_inputs(node: &[u8], names: &mut HashSet<String>) -> Result<()> {
+    let mut reader = Reader::new(node);
+    while let Some((number, field)) = reader.next_field()? {
+        let (5, Some(attribute)) = (number, field.bytes()) else { continue };
+        let mut attribute = Reader::new(attribute);
+        while let Some((number, field)) = attribute.next_field()? {
+            // AttributeProto.g and AttributeProto.graphs
+            let (6 | 11, Some(graph)) = (number, field.bytes()) else { continue };
+            let mut graph = Reader::new(graph);
+            while let Some((number, field)) = graph.next_field()? {
+                let (1, Some(inner)) = (number, field.bytes()) else { continue };
+                names.extend(parse_node(inner)?.inputs);
+                collect_subgraph_inputs(inner, names)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn value_info_name(raw: &[u8]) -> Result<String> {
+    let mut reader = Reader::new(raw);
+    while let Some((number, field)) = reader.next_field()? {
+        if number == 1 {
+            return Ok(field.string());
+        }
+    }
+    Ok(String::new())
+}
+
+/// Decode a float32 initializer stored inside the model; anything else returns `None`
+fn parse_float_initializer(raw: &[u8]) -> Result<Option<Weight>> {
+    let mut dims = Vec::new();
+    let mut data_type = 0;
+    let mut name = String::new();
+    let mut raw_data = None;
+    let mut float_data = Vec::new();
+    let mut external = false;
+
+    let mut reader = Reader::new(raw);
+    while let Some((number, field)) = reader.next_field()? {
+        match number {
+            1 => push_ints(&field, &mut dims)?,
+            2 => data_type = field.varint().unwrap_or_default(),
+            4 => match field {
+                Field::Fixed32(bits) => float_data.push(f32::from_bits(bits)),
+                Field::Bytes(packed) => float_data.extend(packed.chunks_exact(4).map(le_f32)),
+                _ => {}
+            },
+            8 => name = field.string(),
+            9 => raw_data = field.bytes(),
+            14 => external = field.varint() == Some(1),
+            _ => {}
+        }
+    }
+    if data_type != FLOAT || external {
+        return Ok(None);
+    }
+
+    let data = match raw_data {
+        Some(bytes) => bytes.chunks_exact(4).map(le_f32).collect(),
+        None => float_data,
+    };
+    let elements: i64 = dims.iter().product();
+    if elements < 0 || data.len() != elements as usize {
+        return Ok(None);
+    }
+    Ok(Some(Weight { name, dims, data }))
+}
+
+fn le_f32(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn encode_tensor(name: &str, data_type: u64, dims: &[i64], raw_data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(raw_data.len() + name.len() + 16);
+    for &dim in dims {
+        write_varint_field(&mut out, 1, dim as u64);
+    }
+    write_varint_field(&mut out, 2, data_type);
+    write_bytes_field(&mut out, 8, name.as_bytes());
+    write_bytes_field(&mut out, 9, raw_data);
+    out
+}
+
+/// A `NodeProto` in the default domain with integer attributes
+fn encode_node(name: &str, op_type: &str, inputs: &[&str], outputs: &[&str], attributes: &[(&str, i64)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for input in inputs {
+        write_bytes_field(&mut out, 1, input.as_bytes());
+    }
+    for output in outputs {
+        write_bytes_field(&mut out, 2, output.as_bytes());
+    }
+    write_bytes_field(&mut out, 3, name.as_bytes());
+    write_bytes_field(&mut out, 4, op_type.as_bytes());
+    for (attribute_name, value) in attributes {
+        // AttributeProto: name, i, type = INT
+        let mut attribute = Vec::new();
+        write_bytes_field(&mut attribute, 1, attribute_name.as_bytes());
+        write_varint_field(&mut attribute, 3, *value as u64);
+        write_varint_field(&mut attribute, 20, 2);
+        write_bytes_field(&mut out, 5, &attribute);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetric_quantization_scales_to_the_largest_magnitude() {
+        let (quantized, scale) = quantize_symmetric(&[-1.27, 0.635, 1.27, 0.0]);
+        assert!((scale - 0.01).abs() < 1e-7, "{}", scale);
+        let values: Vec<i8> = quantized.iter().map(|&b| b as i8).collect();
+        assert_eq!(values, [-127, 64, 127, 0]);
+    }
+
+    #[test]
+    fn symmetric_quantization_ignores_non_finite_values_for_the_scale() {
+        let (quantized, scale) = quantize_symmetric(&[0.5, f32::INFINITY, f32::NAN]);
+        assert!((scale - 0.5 / 127.0).abs() < 1e-9, "{}", scale);
+        let values: Vec<i8> = quantized.iter().map(|&b| b as i8).collect();
+        assert_eq!(values, [127, 127, 0]);
+
+        let (quantized, scale) = quantize_symmetric(&[0.0; 4]);
+        assert_eq!(scale, 1.0);
+        assert_eq!(quantized, [0; 4]);
+    }
+}
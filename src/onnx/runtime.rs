@@ -0,0 +1,611 @@
+// ONNX Runtime backend (the `onnxruntime` feature)
+
+use super::signature::ElementType;
+use crate::tts::LogLevel;
+use super::{AccelerationProvider, BatchItem, InferenceBackend, ModelOutputs, ModelSignature, OptimizationLevel, ProviderOptions,
+    ProviderStatus, SessionOptions, TensorSpec};
+use std::borrow::Cow;
+use ndarray::{ArrayBase, IxDyn, OwnedRepr};
+use ort::{
+    session::{Session, SessionInputValue, SessionInputs, SessionOutputs, builder::{GraphOptimizationLevel, SessionBuilder}},
+    memory::Allocator,
+    tensor::TensorElementType,
+    value::{DynTensor, DynTensorValueType, Tensor, Value},
+};
+
+#[cfg(feature = "cuda")]
+use ort::execution_providers::cuda::CUDAExecutionProvider;
+#[cfg(feature = "coreml")]
+use ort::execution_providers::coreml::CoreMLExecutionProvider;
+#[cfg(feature = "directml")]
+use ort::execution_providers::directml::DirectMLExecutionProvider;
+#[cfg(feature = "tensorrt")]
+use ort::execution_providers::tensorrt::TensorRTExecutionProvider;
+#[cfg(feature = "rocm")]
+use ort::execution_providers::rocm::ROCmExecutionProvider;
+#[cfg(feature = "openvino")]
+use ort::execution_providers::openvino::OpenVINOExecutionProvider;
+#[cfg(feature = "onednn")]
+use ort::execution_providers::onednn::OneDNNExecutionProvider;
+#[cfg(feature = "webgpu")]
+use ort::execution_providers::webgpu::WebGPUExecutionProvider;
+use ort::execution_providers::cpu::CPUExecutionProvider;
+use ort::execution_providers::{ExecutionProvider, RegisterError};
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A loaded KittenTTS ONNX model.
+///
+/// `Session::run` needs exclusive access, so the model keeps a pool of
+/// sessions and `infer` takes `&self`: up to `SessionOptions::pool_size`
+/// requests run in parallel, further callers wait for a free session.
+pub struct KittenOnnx {
+    sessions: Vec<Mutex<Session>>,
+    next_session: AtomicUsize,
+    provider_status: ProviderStatus,
+    signature: ModelSignature,
+    log_level: LogLevel,
+}
+
+impl From<OptimizationLevel> for GraphOptimizationLevel {
+    fn from(level: OptimizationLevel) -> Self {
+        match level {
+            OptimizationLevel::Disable => GraphOptimizationLevel::Disable,
+            OptimizationLevel::Basic => GraphOptimizationLevel::Level1,
+            OptimizationLevel::Extended => GraphOptimizationLevel::Level2,
+            OptimizationLevel::All => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+impl SessionOptions {
+    fn apply(&self, mut builder: SessionBuilder) -> Result<SessionBuilder> {
+        if let Some(threads) = self.intra_threads {
+            builder = builder.with_intra_threads(threads)?;
+        }
+        if let Some(threads) = self.inter_threads {
+            builder = builder.with_inter_threads(threads)?;
+        }
+        if let Some(level) = self.optimization_level {
+            builder = builder.with_optimization_level(level.into())?;
+        }
+        if let Some(enable) = self.memory_pattern {
+            builder = builder.with_memory_pattern(enable)?;
+        }
+        if self.deterministic_compute {
+            builder = builder.with_deterministic_compute(true)?;
+        }
+        if self.parallel_execution {
+            builder = builder.with_parallel_execution(true)?;
+        }
+        Ok(builder)
+    }
+}
+
+impl KittenOnnx {
+    pub fn new(model_path: &str) -> Result<Self> {
+        Self::with_provider(model_path, AccelerationProvider::Cpu)
+    }
+
+    pub fn with_provider(model_path: &str, provider: AccelerationProvider) -> Result<Self> {
+        Self::with_options(model_path, provider, &SessionOptions::default())
+    }
+
+    pub fn with_options(model_path: &str, provider: AccelerationProvider, options: &SessionOptions) -> Result<Self> {
+        let pool_size = options.pool_size.unwrap_or(1).max(1);
+
+        let (first, provider_status) = Self::create_session(model_path, provider, options)?;
+        let mut sessions = vec![Mutex::new(first)];
+        for _ in 1..pool_size {
+            let (session, _) = Self::create_session(model_path, provider, options)?;
+            sessions.push(Mutex::new(session));
+        }
+        if pool_size > 1 && options.log_level >= LogLevel::Info {
+            eprintln!("Loaded {} inference sessions", pool_size);
+        }
+
+        let signature = {
+            let session = sessions[0].lock().unwrap_or_else(PoisonError::into_inner);
+            ModelSignature::from_session(&session)?
+        };
+
+        Ok(KittenOnnx {
+            sessions,
+            next_session: AtomicUsize::new(0),
+            provider_status,
+            signature,
+            log_level: options.log_level,
+        })
+    }
+
+    fn create_session(
+        model_path: &str,
+        provider: AccelerationProvider,
+        options: &SessionOptions,
+    ) -> Result<(Session, ProviderStatus)> {
+        let mut builder = options.apply(SessionBuilder::new()?)?;
+
+        // Register the requested provider explicitly so we know whether it took
+        let info = options.log_level >= LogLevel::Info;
+        let provider_status = match register_provider(&mut builder, provider, &options.provider_options) {
+            Ok(()) => {
+                if info {
+                    eprintln!("Using {} execution provider", provider);
+                }
+                ProviderStatus { requested: provider, active: provider, fallback_reason: None }
+            }
+            Err(reason) => {
+                if info {
+                    eprintln!("{} execution provider unavailable ({}), falling back to CPU", provider, reason);
+                }
+                ProviderStatus { requested: provider, active: AccelerationProvider::Cpu, fallback_reason: Some(reason) }
+            }
+        };
+
+        // CPU always goes last so operators unsupported by an accelerator still run
+        CPUExecutionProvider::default().register(&mut builder)?;
+
+        let session = builder.commit_from_file(model_path)?;
+        Ok((session, provider_status))
+    }
+
+    /// Check out a session: the first idle one, starting round-robin, or wait
+    /// for the next in line if all are busy.
+    fn acquire_session(&self) -> MutexGuard<'_, Session> {
+        let n = self.sessions.len();
+        let start = self.next_session.fetch_add(1, Ordering::Relaxed) % n;
+        for i in 0..n {
+            if let Ok(session) = self.sessions[(start + i) % n].try_lock() {
+                return session;
+            }
+        }
+        // A panic mid-run can't leave the session itself in a bad state
+        self.sessions[start].lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of sessions available for concurrent inference
+    pub fn pool_size(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn provider_status(&self) -> &ProviderStatus {
+        &self.provider_status
+    }
+
+    /// Input/output layout of the loaded model
+    pub fn signature(&self) -> &ModelSignature {
+        &self.signature
+    }
+
+    pub fn infer(
+        &self,
+        input_ids: Vec<Vec<i64>>,
+        style: Vec<f32>,
+        speed: f32,
+    ) -> Result<ArrayBase<OwnedRepr<f32>, IxDyn>> {
+        Ok(self.infer_outputs(input_ids, style, speed)?.waveform)
+    }
+
+    /// Like `infer`, but also returns the duration and any other outputs the model exports
+    pub fn infer_outputs(&self, input_ids: Vec<Vec<i64>>, style: Vec<f32>, speed: f32) -> Result<ModelOutputs> {
+        anyhow::ensure!(input_ids.len() == 1, "infer takes one token sequence; use infer_batch for several");
+        let tokens = input_ids.into_iter().next().unwrap_or_default();
+        let shape = [1, tokens.len()];
+        let inputs = self.build_inputs(tokens, shape, style, 1, vec![speed])?;
+
+        let mut session = self.acquire_session();
+        let outputs: SessionOutputs = session.run(SessionInputs::from(inputs))?;
+
+        let waveform = extract_array(&outputs, &self.signature.waveform.name)?;
+        let durations = match &self.signature.durations {
+            Some(spec) => Some(extract_f32(&outputs[spec.name.as_str()])?),
+            None => None,
+        };
+        let extra = self
+            .signature
+            .extra_outputs
+            .iter()
+            .filter_map(|spec| Some((spec.name.clone(), extract_array(&outputs, &spec.name).ok()?)))
+            .collect();
+
+        Ok(ModelOutputs { waveform, durations, extra })
+    }
+
+    /// Turn token ids, style vectors and speeds into named input tensors of the model's dtypes
+    fn build_inputs(
+        &self,
+        tokens: Vec<i64>,
+        tokens_shape: [usize; 2],
+        styles: Vec<f32>,
+        batch: usize,
+        speeds: Vec<f32>,
+    ) -> Result<Vec<(Cow<'static, str>, SessionInputValue<'static>)>> {
+        let signature = &self.signature;
+        let style_dim = styles.len() / batch.max(1);
+        if let Some(expected) = signature.style_dim() {
+            anyhow::ensure!(
+                style_dim == expected,
+                "Style vector has {} values but model input {} expects {}",
+                style_dim,
+                signature.style,
+                expected
+            );
+        }
+
+        let input_ids: Value = match signature.input_ids.dtype {
+            ElementType::Int32 => {
+                let tokens: Vec<i32> = tokens.into_iter().map(|t| t as i32).collect();
+                Tensor::from_array((tokens_shape, tokens))?.into_dyn()
+            }
+            _ => Tensor::from_array((tokens_shape, tokens))?.into_dyn(),
+        };
+        let style = float_input(&signature.style, signature.style_shape(batch, style_dim), styles)?;
+        let speed = float_input(&signature.speed, signature.speed_shape(batch), speeds)?;
+
+        Ok(vec![
+            (Cow::Owned(signature.input_ids.name.clone()), SessionInputValue::Owned(input_ids)),
+            (Cow::Owned(signature.style.name.clone()), SessionInputValue::Owned(style)),
+            (Cow::Owned(signature.speed.name.clone()), SessionInputValue::Owned(speed)),
+        ])
+    }
+
+    /// Run several utterances, batching those that can share a model run.
+    ///
+    /// The model takes no attention mask, so there is no padding: only items with the same
+    /// number of tokens go into one run, and each distinct length is a separate run. The
+    /// model still pads the audio of a batch to its longest item, so each waveform is cut
+    /// back to the length its predicted durations account for; models that don't export
+    /// durations, or can't batch, run the items one at a time. Each item keeps its own
+    /// style vector and speed, and the results are returned in the order of `items`.
+    pub fn infer_batch(&self, items: &[BatchItem]) -> Result<Vec<Vec<f32>>> {
+        let mut lengths: Vec<usize> = items.iter().map(|item| item.input_ids.len()).collect();
+        lengths.sort_unstable();
+        lengths.dedup();
+
+        let mut waveforms = vec![Vec::new(); items.len()];
+        for len in lengths {
+            let indices: Vec<usize> = (0..items.len()).filter(|&i| items[i].input_ids.len() == len).collect();
+            let group: Vec<BatchItem> = indices.iter().map(|&i| items[i].clone()).collect();
+            for (i, waveform) in indices.into_iter().zip(self.infer_same_length(&group)?) {
+                waveforms[i] = waveform;
+            }
+        }
+        Ok(waveforms)
+    }
+
+    /// Run items of equal token length in one batch, or one at a time if the model can't batch
+    fn infer_same_length(&self, items: &[BatchItem]) -> Result<Vec<Vec<f32>>> {
+        let one_at_a_time = || -> Result<Vec<Vec<f32>>> {
+            items
+                .iter()
+                .map(|item| Ok(self.infer(vec![item.input_ids.clone()], item.style.clone(), item.speed)?.into_iter().collect()))
+                .collect()
+        };
+        if items.len() < 2 || !self.signature.supports_batching() || self.signature.durations.is_none() {
+            return one_at_a_time();
+        }
+        match self.run_batch(items) {
+            // Some exports fix the batch dimension to 1 without saying so; run those one item at a time
+            Err(e) => {
+                if self.log_level >= LogLevel::Info {
+                    eprintln!("Batched inference failed ({}), running items one at a time", e);
+                }
+                one_at_a_time()
+            }
+            result => result,
+        }
+    }
+
+    /// One model run over `items`, which all have the same number of tokens. The batch's
+    /// waveforms are split back per item and trimmed to the samples the item's predicted
+    /// durations account for.
+    fn run_batch(&self, items: &[BatchItem]) -> Result<Vec<Vec<f32>>> {
+        let batch = items.len();
+        let tokens = items[0].input_ids.len();
+        let style_dim = items[0].style.len();
+        anyhow::ensure!(
+            items.iter().all(|item| item.input_ids.len() == tokens),
+            "All batch items must have the same number of tokens"
+        );
+        anyhow::ensure!(
+            items.iter().all(|item| item.style.len() == style_dim),
+            "All batch items must use style vectors of the same length"
+        );
+        let durations_spec = self.signature.durations.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Model exports no durations to split a batch by"))?;
+
+        // input_ids [batch, tokens], styles [batch, style_dim] and speeds [batch]
+        let input_ids: Vec<i64> = items.iter().flat_map(|item| item.input_ids.iter().copied()).collect();
+        let styles: Vec<f32> = items.iter().flat_map(|item| item.style.iter().copied()).collect();
+        let speeds: Vec<f32> = items.iter().map(|item| item.speed).collect();
+        let inputs = self.build_inputs(input_ids, [batch, tokens], styles, batch, speeds)?;
+
+        let mut session = self.acquire_session();
+        let outputs: SessionOutputs = session.run(SessionInputs::from(inputs))?;
+
+        let waveforms = extract_f32(&outputs[self.signature.waveform.name.as_str()])?;
+        anyhow::ensure!(
+            waveforms.len() % batch == 0,
+            "Model returned {} samples, which doesn't split into {} items",
+            waveforms.len(),
+            batch
+        );
+        let samples_per_item = waveforms.len() / batch;
+
+        let durations = extract_f32(&outputs[durations_spec.name.as_str()])?;
+        anyhow::ensure!(
+            durations.len() == batch * tokens,
+            "Model returned {} durations for {} items of {} tokens",
+            durations.len(),
+            batch,
+            tokens
+        );
+        // The longest item (in frames) fills the padded waveform, which gives samples per frame
+        let frames: Vec<f32> = durations.chunks(tokens.max(1)).map(|row| row.iter().sum()).collect();
+        let max_frames = frames.iter().copied().fold(0.0f32, f32::max);
+        let samples_per_frame = if max_frames > 0.0 { samples_per_item as f32 / max_frames } else { 0.0 };
+
+        Ok(waveforms
+            .chunks(samples_per_item)
+            .zip(frames)
+            .map(|(waveform, frames)| {
+                let len = ((frames * samples_per_frame).round() as usize).min(samples_per_item);
+                waveform[..len].to_vec()
+            })
+            .collect())
+    }
+}
+
+impl InferenceBackend for KittenOnnx {
+    fn signature(&self) -> &ModelSignature {
+        &self.signature
+    }
+
+    fn provider_status(&self) -> &ProviderStatus {
+        &self.provider_status
+    }
+
+    fn infer_outputs(&self, input_ids: Vec<Vec<i64>>, style: Vec<f32>, speed: f32) -> Result<ModelOutputs> {
+        KittenOnnx::infer_outputs(self, input_ids, style, speed)
+    }
+
+    fn infer_batch(&self, items: &[BatchItem]) -> Result<Vec<Vec<f32>>> {
+        KittenOnnx::infer_batch(self, items)
+    }
+}
+
+/// Read an output as f32 regardless of whether the model exports it as int or float
+fn extract_f32(value: &ort::value::DynValue) -> Result<Vec<f32>> {
+    match value.dtype().tensor_type() {
+        Some(TensorElementType::Float16) => {
+            let tensor = value.downcast_ref::<DynTensorValueType>()?;
+            let len = tensor.shape().num_elements();
+            let ptr = tensor.data_ptr().cast::<u16>();
+            if len == 0 || ptr.is_null() {
+                return Ok(Vec::new());
+            }
+            // Safety: a CPU tensor of `len` float16 elements, alive for the duration of this borrow
+            let bits = unsafe { std::slice::from_raw_parts(ptr, len) };
+            Ok(bits.iter().map(|&b| f16_to_f32(b)).collect())
+        }
+        Some(TensorElementType::Float64) => {
+            let (_, data) = value.try_extract_tensor::<f64>()?;
+            Ok(data.iter().map(|&d| d as f32).collect())
+        }
+        Some(TensorElementType::Int64) => {
+            let (_, data) = value.try_extract_tensor::<i64>()?;
+            Ok(data.iter().map(|&d| d as f32).collect())
+        }
+        _ => Ok(value.try_extract_tensor::<f32>()?.1.to_vec()),
+    }
+}
+
+/// An output as an f32 array with its shape
+fn extract_array(outputs: &SessionOutputs, name: &str) -> Result<ArrayBase<OwnedRepr<f32>, IxDyn>> {
+    let value = outputs
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("Model did not produce output '{}'", name))?;
+    let dims: Vec<usize> = match value.dtype().tensor_shape() {
+        Some(shape) => shape.iter().map(|&d| d.max(0) as usize).collect(),
+        None => anyhow::bail!("Model output '{}' is not a tensor", name),
+    };
+    Ok(ArrayBase::from_shape_vec(IxDyn(&dims), extract_f32(value)?)?)
+}
+
+/// A float input tensor in the dtype the model declares
+fn float_input(spec: &TensorSpec, shape: Vec<usize>, data: Vec<f32>) -> Result<Value> {
+    match spec.dtype {
+        ElementType::Float32 => Ok(Tensor::from_array((shape, data))?.into_dyn()),
+        ElementType::Float64 => {
+            let data: Vec<f64> = data.into_iter().map(f64::from).collect();
+            Ok(Tensor::from_array((shape, data))?.into_dyn())
+        }
+        ElementType::Float16 => {
+            let mut tensor = DynTensor::new(&Allocator::default(), TensorElementType::Float16, shape)?;
+            let ptr = tensor.data_ptr_mut().cast::<u16>();
+            if !data.is_empty() && !ptr.is_null() {
+                // Safety: the tensor was just allocated on the CPU with exactly `data.len()` elements
+                let bits = unsafe { std::slice::from_raw_parts_mut(ptr, data.len()) };
+                for (bit, &value) in bits.iter_mut().zip(&data) {
+                    *bit = f32_to_f16(value);
+                }
+            }
+            Ok(tensor.into_dyn())
+        }
+        other => anyhow::bail!(
+            "Model input {} uses {} but kittenx only feeds float32, float16 or float64 there; re-export the model with a float32 {} input",
+            spec,
+            other,
+            spec.name
+        ),
+    }
+}
+
+/// IEEE 754 half precision bits for `value`, rounding to nearest even
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exp == 0xff {
+        // Infinity stays infinity, NaN stays (quiet) NaN
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let exp = exp - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exp <= 0 {
+        // Subnormal in half precision, or too small and flushed to zero
+        if exp < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exp) as u32;
+        let half = mantissa >> shift;
+        let round = (mantissa >> (shift - 1)) & 1;
+        return sign | (half + round) as u16;
+    }
+
+    let mut half = ((exp as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    if rest > 0x1000 || (rest == 0x1000 && half & 1 == 1) {
+        // May carry into the exponent, which correctly rounds up to the next power of two or infinity
+        half += 1;
+    }
+    sign | half as u16
+}
+
+/// `f32` value of IEEE 754 half precision bits
+fn f16_to_f32(bits: u16) -> f32 {
+    let negative = bits & 0x8000 != 0;
+    let exp = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+    let magnitude = match exp {
+        0 => mantissa as f32 / 16_777_216.0,
+        0x1f => f32::from_bits(0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(((exp + 112) << 23) | (mantissa << 13)),
+    };
+    if negative { -magnitude } else { magnitude }
+}
+
+/// Register a single accelerator on the builder, returning why it failed if it did
+// Builds without any accelerator features only use `provider`
+#[allow(unused_variables)]
+fn register_provider(
+    builder: &mut SessionBuilder,
+    provider: AccelerationProvider,
+    options: &ProviderOptions,
+) -> std::result::Result<(), String> {
+    let result: std::result::Result<(), RegisterError> = match provider {
+        AccelerationProvider::Cpu => Ok(()),
+        #[cfg(feature = "cuda")]
+        AccelerationProvider::Cuda => {
+            let mut ep = CUDAExecutionProvider::default();
+            if let Some(id) = options.device_id {
+                ep = ep.with_device_id(id);
+            }
+            ep.register(builder)
+        }
+        #[cfg(feature = "coreml")]
+        AccelerationProvider::CoreML => CoreMLExecutionProvider::default().register(builder),
+        #[cfg(feature = "directml")]
+        AccelerationProvider::DirectML => {
+            let mut ep = DirectMLExecutionProvider::default();
+            if let Some(id) = options.device_id {
+                ep = ep.with_device_id(id);
+            }
+            ep.register(builder)
+        }
+        #[cfg(feature = "tensorrt")]
+        AccelerationProvider::TensorRT => {
+            let mut ep = TensorRTExecutionProvider::default().with_fp16(options.fp16);
+            if let Some(id) = options.device_id {
+                ep = ep.with_device_id(id);
+            }
+            if let Some(path) = &options.tensorrt_engine_cache_path {
+                ep = ep
+                    .with_engine_cache(true)
+                    .with_engine_cache_path(path.display());
+            }
+            ep.register(builder)
+        }
+        #[cfg(feature = "rocm")]
+        AccelerationProvider::ROCm => {
+            let mut ep = ROCmExecutionProvider::default();
+            if let Some(id) = options.device_id {
+                ep = ep.with_device_id(id);
+            }
+            ep.register(builder)
+        }
+        #[cfg(feature = "openvino")]
+        AccelerationProvider::OpenVINO => {
+            let mut ep = OpenVINOExecutionProvider::default();
+            if let Some(device_type) = &options.openvino_device_type {
+                ep = ep.with_device_type(device_type);
+            }
+            if options.fp16 {
+                ep = ep.with_precision("FP16");
+            }
+            ep.register(builder)
+        }
+        #[cfg(feature = "onednn")]
+        AccelerationProvider::OneDNN => OneDNNExecutionProvider::default().register(builder),
+        #[cfg(feature = "webgpu")]
+        AccelerationProvider::WebGPU => {
+            let mut ep = WebGPUExecutionProvider::default();
+            if let Some(id) = options.device_id {
+                ep = ep.with_device_id(id);
+            }
+            ep.register(builder)
+        }
+        #[allow(unreachable_patterns)]
+        other => return Err(format!("kittenx was built without the `{}` feature", other.name())),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f16_round_trips_every_finite_value() {
+        for bits in (0..0x7c00u16).chain(0x8000..0xfc00) {
+            assert_eq!(f32_to_f16(f16_to_f32(bits)), bits, "{:#06x}", bits);
+        }
+    }
+
+    #[test]
+    fn f32_to_f16_edge_cases() {
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        // Subnormals: the smallest half, and values too small for it flush to zero
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(1023.0 * 2f32.powi(-24)), 0x03ff);
+        assert_eq!(f32_to_f16(2f32.powi(-26)), 0x0000);
+        // Overflow: rounding up past the largest half, and far out of range
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert_eq!(f32_to_f16(-1e6), 0xfc00);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        let nan = f32_to_f16(f32::NAN);
+        assert!(nan & 0x7c00 == 0x7c00 && nan & 0x3ff != 0, "{:#06x}", nan);
+    }
+
+    #[test]
+    fn f16_to_f32_edge_cases() {
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x8400), -(2f32.powi(-14)));
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+        assert!(f16_to_f32(0x8000).is_sign_negative());
+    }
+}
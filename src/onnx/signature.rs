@@ -0,0 +1,316 @@
+// Mapping between the model's ONNX inputs/outputs and what kittenx feeds and reads
+// Read from the session at load time, so re-exported or third-party
+// KittenTTS-compatible models work even if they rename or reshape tensors.
+
+use super::proto::{elem_type_name, Dim, ModelSummary, TensorInfo};
+use anyhow::Result;
+#[cfg(feature = "onnxruntime")]
+use ort::{session::Session, tensor::TensorElementType, value::ValueType};
+use std::fmt;
+
+/// Element type of a model tensor, independent of the inference runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    Float32,
+    Float16,
+    Bfloat16,
+    Float64,
+    Int64,
+    Int32,
+    /// Any other ONNX `TensorProto.DataType`
+    Other(i32),
+}
+
+impl ElementType {
+    pub fn from_onnx(elem_type: i32) -> Self {
+        match elem_type {
+            1 => Self::Float32,
+            10 => Self::Float16,
+            16 => Self::Bfloat16,
+            11 => Self::Float64,
+            7 => Self::Int64,
+            6 => Self::Int32,
+            other => Self::Other(other),
+        }
+    }
+
+    /// ONNX `TensorProto.DataType` number
+    pub fn onnx(&self) -> i32 {
+        match self {
+            Self::Float32 => 1,
+            Self::Float16 => 10,
+            Self::Bfloat16 => 16,
+            Self::Float64 => 11,
+            Self::Int64 => 7,
+            Self::Int32 => 6,
+            Self::Other(other) => *other,
+        }
+    }
+}
+
+#[cfg(feature = "onnxruntime")]
+impl From<TensorElementType> for ElementType {
+    fn from(dtype: TensorElementType) -> Self {
+        match dtype {
+            TensorElementType::Float32 => Self::Float32,
+            TensorElementType::Float16 => Self::Float16,
+            TensorElementType::Bfloat16 => Self::Bfloat16,
+            TensorElementType::Float64 => Self::Float64,
+            TensorElementType::Int64 => Self::Int64,
+            TensorElementType::Int32 => Self::Int32,
+            TensorElementType::Uint8 => Self::Other(2),
+            TensorElementType::Int8 => Self::Other(3),
+            TensorElementType::Uint16 => Self::Other(4),
+            TensorElementType::Int16 => Self::Other(5),
+            TensorElementType::String => Self::Other(8),
+            TensorElementType::Bool => Self::Other(9),
+            TensorElementType::Uint32 => Self::Other(12),
+            TensorElementType::Uint64 => Self::Other(13),
+            _ => Self::Other(0),
+        }
+    }
+}
+
+impl fmt::Display for ElementType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(elem_type_name(self.onnx()))
+    }
+}
+
+/// Name, element type and shape (`-1` for dynamic dimensions) of a model input or output
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorSpec {
+    pub name: String,
+    pub dtype: ElementType,
+    pub shape: Vec<i64>,
+}
+
+impl TensorSpec {
+    #[cfg(feature = "onnxruntime")]
+    fn from_value_type(name: &str, value_type: &ValueType) -> Option<Self> {
+        Some(Self {
+            name: name.to_string(),
+            dtype: value_type.tensor_type()?.into(),
+            shape: value_type.tensor_shape()?.iter().copied().collect(),
+        })
+    }
+
+    fn from_info(info: &TensorInfo) -> Self {
+        Self {
+            name: info.name.clone(),
+            dtype: ElementType::from_onnx(info.elem_type),
+            shape: info.dims.iter().map(|d| if let Dim::Fixed(n) = d { *n } else { -1 }).collect(),
+        }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Fixed size of the batch (first) dimension, if the model pins it
+    fn fixed_batch(&self) -> Option<i64> {
+        self.shape.first().copied().filter(|&d| d > 0)
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(self.dtype, ElementType::Int64 | ElementType::Int32)
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(
+            self.dtype,
+            ElementType::Float32 | ElementType::Float16 | ElementType::Float64 | ElementType::Bfloat16
+        )
+    }
+
+    fn name_contains(&self, patterns: &[&str]) -> bool {
+        let name = self.name.to_lowercase();
+        patterns.iter().any(|p| name.contains(p))
+    }
+}
+
+impl fmt::Display for TensorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dims: Vec<String> = self
+            .shape
+            .iter()
+            .map(|&d| if d < 0 { "?".to_string() } else { d.to_string() })
+            .collect();
+        write!(f, "{}: {}[{}]", self.name, self.dtype, dims.join(", "))
+    }
+}
+
+/// Which model tensors play which role
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSignature {
+    /// Phoneme token ids, `[batch, tokens]`
+    pub input_ids: TensorSpec,
+    /// Voice style vector, `[batch, style_dim]` (or `[style_dim]`)
+    pub style: TensorSpec,
+    /// Speed factor, `[batch]`, `[batch, 1]` or a scalar
+    pub speed: TensorSpec,
+    /// Generated audio
+    pub waveform: TensorSpec,
+    /// Predicted per-token durations, when the model exports them
+    pub durations: Option<TensorSpec>,
+    /// Any other outputs, returned as-is by `KittenOnnx::infer_outputs`
+    pub extra_outputs: Vec<TensorSpec>,
+}
+
+const EXPECTED: &str = "kittenx expects input_ids: int64[batch, tokens], style: float32[batch, style_dim], speed: float32[batch]";
+
+impl ModelSignature {
+    #[cfg(feature = "onnxruntime")]
+    pub fn from_session(session: &Session) -> Result<Self> {
+        let inputs: Vec<TensorSpec> = session
+            .inputs
+            .iter()
+            .map(|input| {
+                TensorSpec::from_value_type(&input.name, &input.input_type).ok_or_else(|| {
+                    anyhow::anyhow!("Model input '{}' is a {}, not a tensor. {}", input.name, input.input_type, EXPECTED)
+                })
+            })
+            .collect::<Result<_>>()?;
+        let outputs: Vec<TensorSpec> = session
+            .outputs
+            .iter()
+            .filter_map(|output| TensorSpec::from_value_type(&output.name, &output.output_type))
+            .collect();
+        Self::from_specs(inputs, outputs)
+    }
+
+    /// Signature from the graph inputs and outputs declared in the model file, for
+    /// runtimes that don't report them
+    pub fn from_summary(summary: &ModelSummary) -> Result<Self> {
+        Self::from_specs(
+            summary.inputs.iter().map(TensorSpec::from_info).collect(),
+            summary.outputs.iter().map(TensorSpec::from_info).collect(),
+        )
+    }
+
+    pub fn from_specs(inputs: Vec<TensorSpec>, outputs: Vec<TensorSpec>) -> Result<Self> {
+        let found = || -> String { inputs.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ") };
+        let mut remaining: Vec<&TensorSpec> = inputs.iter().collect();
+
+        // Prefer names, then fall back to dtype and rank
+        let input_ids = take(&mut remaining, |t| t.is_integer() && t.name_contains(&["input_ids", "token", "ids"]))
+            .or_else(|| take(&mut remaining, TensorSpec::is_integer))
+            .ok_or_else(|| anyhow::anyhow!("Model has no integer token input (found: {}). {}", found(), EXPECTED))?;
+        let style = take(&mut remaining, |t| t.is_float() && t.name_contains(&["style", "ref", "voice", "speaker"]))
+            .or_else(|| take(&mut remaining, |t| t.is_float() && t.rank() >= 2))
+            .ok_or_else(|| anyhow::anyhow!("Model has no style vector input (found: {}). {}", found(), EXPECTED))?;
+        let speed = take(&mut remaining, |t| t.name_contains(&["speed", "rate", "length_scale"]))
+            .or_else(|| take(&mut remaining, |t| t.is_float() && t.rank() <= 2))
+            .ok_or_else(|| anyhow::anyhow!("Model has no speed input (found: {}). {}", found(), EXPECTED))?;
+        if let Some(extra) = remaining.first() {
+            anyhow::bail!("Model has an input kittenx can't fill: {}. {}", extra, EXPECTED);
+        }
+
+        anyhow::ensure!(
+            input_ids.rank() == 2,
+            "Model input {} should be 2-D [batch, tokens]. {}",
+            input_ids,
+            EXPECTED
+        );
+        anyhow::ensure!(
+            matches!(style.rank(), 1 | 2),
+            "Model input {} should be [batch, style_dim] or [style_dim]. {}",
+            style,
+            EXPECTED
+        );
+        anyhow::ensure!(
+            speed.rank() <= 2 && speed.shape.iter().skip(1).all(|&d| d == 1 || d < 0),
+            "Model input {} should be a scalar, [batch] or [batch, 1]. {}",
+            speed,
+            EXPECTED
+        );
+
+        let mut outputs: Vec<TensorSpec> = outputs;
+        let waveform_index = outputs
+            .iter()
+            .position(|o| o.is_float() && o.name_contains(&["wav", "audio"]))
+            .or_else(|| outputs.iter().position(TensorSpec::is_float))
+            .ok_or_else(|| anyhow::anyhow!("Model has no floating point output to read audio from"))?;
+        let waveform = outputs.remove(waveform_index);
+        let durations = outputs
+            .iter()
+            .position(|o| o.name_contains(&["dur"]))
+            .map(|i| outputs.remove(i));
+
+        Ok(Self {
+            input_ids: input_ids.clone(),
+            style: style.clone(),
+            speed: speed.clone(),
+            waveform,
+            durations,
+            extra_outputs: outputs,
+        })
+    }
+
+    /// Length of the style vector the model expects, if fixed
+    pub fn style_dim(&self) -> Option<usize> {
+        self.style.shape.last().copied().filter(|&d| d > 0).map(|d| d as usize)
+    }
+
+    /// Fail with an actionable message if a voice's style vector doesn't fit the model
+    pub fn check_style(&self, voice: &str, len: usize) -> Result<()> {
+        if let Some(dim) = self.style_dim() {
+            anyhow::ensure!(
+                len == dim,
+                "Voice '{}' has a {}-dim style vector but model input {} expects {}; the voices file doesn't belong to this model",
+                voice,
+                len,
+                self.style,
+                dim
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the model accepts more than one utterance per run
+    pub fn supports_batching(&self) -> bool {
+        let fixed_to_one = |t: &TensorSpec| t.fixed_batch() == Some(1);
+        let speed_fixed = self.speed.rank() > 0 && fixed_to_one(&self.speed);
+        self.style.rank() == 2 && !fixed_to_one(&self.input_ids) && !fixed_to_one(&self.style) && !speed_fixed
+    }
+
+    /// Tensor shape for `batch` speed values
+    pub fn speed_shape(&self, batch: usize) -> Vec<usize> {
+        match self.speed.rank() {
+            0 => Vec::new(),
+            1 => vec![batch],
+            _ => vec![batch, 1],
+        }
+    }
+
+    /// Tensor shape for `batch` style vectors of length `dim`
+    pub fn style_shape(&self, batch: usize, dim: usize) -> Vec<usize> {
+        match self.style.rank() {
+            1 => vec![dim],
+            _ => vec![batch, dim],
+        }
+    }
+}
+
+impl fmt::Display for ModelSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Inputs:")?;
+        writeln!(f, "  {} (tokens)", self.input_ids)?;
+        writeln!(f, "  {} (style)", self.style)?;
+        writeln!(f, "  {} (speed)", self.speed)?;
+        writeln!(f, "Outputs:")?;
+        write!(f, "  {} (waveform)", self.waveform)?;
+        if let Some(durations) = &self.durations {
+            write!(f, "\n  {} (durations)", durations)?;
+        }
+        for extra in &self.extra_outputs {
+            write!(f, "\n  {}", extra)?;
+        }
+        Ok(())
+    }
+}
+
+fn take<'a>(remaining: &mut Vec<&'a TensorSpec>, pred: impl Fn(&TensorSpec) -> bool) -> Option<&'a TensorSpec> {
+    let index = remaining.iter().position(|t| pred(t))?;
+    Some(remaining.remove(index))
+}
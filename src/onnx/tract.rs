@@ -0,0 +1,174 @@
+// Pure-Rust inference with tract (the `tract` feature)
+// Slower than ONNX Runtime, but needs no native library, so it also runs in the
+// browser (see `crate::wasm`) and anywhere ONNX Runtime has no prebuilt binaries.
+
+use super::proto::ModelSummary;
+use super::signature::ElementType;
+use super::{AccelerationProvider, InferenceBackend, ModelOutputs, ModelSignature, ProviderStatus, TensorSpec};
+use anyhow::{Context, Result};
+use ndarray::{ArrayBase, IxDyn, OwnedRepr};
+use std::path::Path;
+use tract_onnx::prelude::*;
+
+/// The three model inputs kittenx fills
+#[derive(Clone, Copy)]
+enum Role {
+    InputIds,
+    Style,
+    Speed,
+}
+
+/// A KittenTTS ONNX model loaded with tract
+pub struct TractOnnx {
+    plan: TypedRunnableModel<TypedModel>,
+    signature: ModelSignature,
+    /// Role of each model input, in the order the plan takes them
+    input_roles: Vec<Role>,
+    /// Names of the model outputs, in the order the plan returns them
+    output_names: Vec<String>,
+    provider_status: ProviderStatus,
+}
+
+impl TractOnnx {
+    pub fn from_file(model_path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(model_path)
+            .with_context(|| format!("Failed to read model {}", model_path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(model: &[u8]) -> Result<Self> {
+        let summary = ModelSummary::parse(model).context("Not a valid ONNX model")?;
+        let signature = ModelSignature::from_summary(&summary)?;
+
+        let input_roles = summary
+            .inputs
+            .iter()
+            .map(|input| {
+                if input.name == signature.input_ids.name {
+                    Role::InputIds
+                } else if input.name == signature.style.name {
+                    Role::Style
+                } else {
+                    Role::Speed
+                }
+            })
+            .collect();
+        let output_names = summary.outputs.iter().map(|output| output.name.clone()).collect();
+
+        let plan = tract_onnx::onnx()
+            .model_for_read(&mut std::io::Cursor::new(model))
+            .context("tract could not load the model")?
+            .into_optimized()
+            .context("tract could not optimize the model")?
+            .into_runnable()?;
+
+        let provider_status = ProviderStatus {
+            requested: AccelerationProvider::Cpu,
+            active: AccelerationProvider::Cpu,
+            fallback_reason: None,
+        };
+        Ok(Self { plan, signature, input_roles, output_names, provider_status })
+    }
+
+    /// Record the provider the caller asked for; tract always runs on the CPU
+    pub fn with_requested_provider(mut self, provider: AccelerationProvider) -> Self {
+        if provider != AccelerationProvider::Cpu {
+            self.provider_status.requested = provider;
+            self.provider_status.fallback_reason = Some("the tract backend only runs on the CPU".to_string());
+        }
+        self
+    }
+}
+
+impl InferenceBackend for TractOnnx {
+    fn signature(&self) -> &ModelSignature {
+        &self.signature
+    }
+
+    fn provider_status(&self) -> &ProviderStatus {
+        &self.provider_status
+    }
+
+    fn infer_outputs(&self, input_ids: Vec<Vec<i64>>, style: Vec<f32>, speed: f32) -> Result<ModelOutputs> {
+        anyhow::ensure!(input_ids.len() == 1, "infer takes one token sequence; use infer_batch for several");
+        let tokens = input_ids.into_iter().next().unwrap_or_default();
+        let signature = &self.signature;
+        if let Some(expected) = signature.style_dim() {
+            anyhow::ensure!(
+                style.len() == expected,
+                "Style vector has {} values but model input {} expects {}",
+                style.len(),
+                signature.style,
+                expected
+            );
+        }
+
+        let input_ids = match signature.input_ids.dtype {
+            ElementType::Int32 => {
+                let tokens: Vec<i32> = tokens.iter().map(|&t| t as i32).collect();
+                Tensor::from_shape(&[1, tokens.len()], &tokens)?
+            }
+            _ => Tensor::from_shape(&[1, tokens.len()], &tokens)?,
+        };
+        let style_shape = signature.style_shape(1, style.len());
+        let style = float_input(&signature.style, &style_shape, &style)?;
+        let speed = float_input(&signature.speed, &signature.speed_shape(1), &[speed])?;
+
+        let mut input_ids = Some(input_ids);
+        let mut style = Some(style);
+        let mut speed = Some(speed);
+        let inputs: TVec<TValue> = self
+            .input_roles
+            .iter()
+            .filter_map(|role| match role {
+                Role::InputIds => input_ids.take(),
+                Role::Style => style.take(),
+                Role::Speed => speed.take(),
+            })
+            .map(TValue::from)
+            .collect();
+        let outputs = self.plan.run(inputs).context("tract inference failed")?;
+
+        let output = |name: &str| -> Result<ArrayBase<OwnedRepr<f32>, IxDyn>> {
+            let index = self
+                .output_names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| anyhow::anyhow!("Model did not produce output '{}'", name))?;
+            let value = outputs
+                .get(index)
+                .ok_or_else(|| anyhow::anyhow!("Model did not produce output '{}'", name))?;
+            let value = value.cast_to::<f32>()?;
+            Ok(ArrayBase::from_shape_vec(IxDyn(value.shape()), value.as_slice::<f32>()?.to_vec())?)
+        };
+
+        let waveform = output(&signature.waveform.name)?;
+        let durations = match &signature.durations {
+            Some(spec) => Some(output(&spec.name)?.into_iter().collect()),
+            None => None,
+        };
+        let extra = signature
+            .extra_outputs
+            .iter()
+            .filter_map(|spec| Some((spec.name.clone(), output(&spec.name).ok()?)))
+            .collect();
+
+        Ok(ModelOutputs { waveform, durations, extra })
+    }
+}
+
+/// A float input tensor in the dtype the model declares
+fn float_input(spec: &TensorSpec, shape: &[usize], data: &[f32]) -> Result<Tensor> {
+    let tensor = Tensor::from_shape(shape, data)?;
+    let datum_type = match spec.dtype {
+        ElementType::Float32 => return Ok(tensor),
+        ElementType::Float16 => DatumType::F16,
+        ElementType::Float64 => DatumType::F64,
+        other => anyhow::bail!(
+            "Model input {} uses {} but kittenx only feeds float32, float16 or float64 there",
+            spec,
+            other
+        ),
+    };
+    Ok(tensor.cast_to_dt(datum_type)?.into_owned())
+}
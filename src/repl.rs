@@ -0,0 +1,175 @@
+// `kittenx repl`: keeps the model loaded and synthesizes each line read from stdin
+// Status messages go to stderr, since stdout may carry the audio itself.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use kittenx::tts::markup::speakable_text;
+use kittenx::utils::AudioFormat;
+use kittenx::{CodeBlocks, InputFormat, KittenTTS, SynthesisRequest};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Where the audio of each line goes
+pub enum Output {
+    /// Cycle through `repl-01` .. `repl-<keep>` in `dir`, overwriting the oldest file
+    Files { dir: PathBuf, keep: usize },
+    Stdout,
+}
+
+/// Options the colon-commands change between lines
+pub struct Settings {
+    pub voice: String,
+    pub speed: f32,
+    pub language: String,
+    pub pitch: f32,
+    pub format: AudioFormat,
+    /// Print the phonemes of each line before synthesizing it
+    pub phonemes: bool,
+    /// Markup stripped from each line
+    pub input_format: InputFormat,
+    pub code_blocks: CodeBlocks,
+}
+
+const HELP: &str = "\
+Type a line of text to synthesize it. Commands:
+  :voice <name>       switch voice (without a name: list voices)
+  :speed <factor>     speech speed (1.0 = normal)
+  :language <code>    espeak language code, or auto to detect it
+  :pitch <semitones>  pitch shift from -12 to 12
+  :format <format>    wav, wav16 or raw
+  :phonemes on|off    print the phonemes of each line
+  :show               print the current settings
+  :quit               exit (also Ctrl-D)";
+
+impl Settings {
+    /// Apply a colon-command (without the colon). Returns false to quit.
+    fn apply(&mut self, command: &str, tts: &KittenTTS) -> Result<bool> {
+        let (name, arg) = match command.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (command, ""),
+        };
+        let require = |what: &str| -> Result<&str> {
+            anyhow::ensure!(!arg.is_empty(), ":{} needs {}", name, what);
+            Ok(arg)
+        };
+
+        match name {
+            "voice" | "v" if arg.is_empty() => eprintln!("Voices: {}", tts.available_voices().join(", ")),
+            "voice" | "v" => {
+                anyhow::ensure!(
+                    tts.available_voices().iter().any(|v| v == arg),
+                    "Voice '{}' not available. Available voices: {}",
+                    arg,
+                    tts.available_voices().join(", ")
+                );
+                self.voice = arg.to_string();
+            }
+            "speed" => {
+                let speed: f32 = require("a factor")?.parse().context("Speed must be a number")?;
+                anyhow::ensure!(speed.is_finite() && speed > 0.0, "Speed must be positive, got {}", speed);
+                self.speed = speed;
+            }
+            "language" | "lang" => self.language = require("a language code")?.to_string(),
+            "pitch" => {
+                let pitch: f32 = require("a number of semitones")?.parse().context("Pitch must be a number")?;
+                anyhow::ensure!((-12.0..=12.0).contains(&pitch), "Pitch must be between -12 and 12 semitones");
+                self.pitch = pitch;
+            }
+            "format" => {
+                self.format = AudioFormat::from_str(require("wav, wav16 or raw")?, true)
+                    .map_err(|_| anyhow::anyhow!("Unknown format '{}': use wav, wav16 or raw", arg))?;
+            }
+            "phonemes" => {
+                self.phonemes = match require("on or off")? {
+                    "on" => true,
+                    "off" => false,
+                    other => anyhow::bail!("Expected on or off, got '{}'", other),
+                }
+            }
+            "show" => eprintln!(
+                "voice {}, speed {}, language {}, pitch {}, format {:?}, phonemes {}",
+                self.voice,
+                self.speed,
+                self.language,
+                self.pitch,
+                self.format,
+                if self.phonemes { "on" } else { "off" }
+            ),
+            "help" | "h" | "?" => eprintln!("{}", HELP),
+            "quit" | "q" | "exit" => return Ok(false),
+            _ => anyhow::bail!("Unknown command :{} (try :help)", name),
+        }
+        Ok(true)
+    }
+}
+
+/// Read lines from stdin until EOF or `:quit`. Errors in one line are reported and the
+/// REPL carries on with the next.
+pub fn run(tts: &KittenTTS, mut settings: Settings, output: Output) -> Result<()> {
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        eprintln!("Type text to synthesize, :help for commands, :quit or Ctrl-D to exit");
+    }
+    if let Output::Files { dir, .. } = &output {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut lines = std::io::stdin().lock().lines();
+    let mut count = 0usize;
+    loop {
+        if interactive {
+            eprint!("{}> ", settings.voice);
+            std::io::stderr().flush()?;
+        }
+        let Some(line) = lines.next() else { break };
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(command) = line.strip_prefix(':') {
+            match settings.apply(command.trim(), tts) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => eprintln!("Error: {:#}", e),
+            }
+            continue;
+        }
+        match speak(tts, &settings, &output, count, line) {
+            Ok(()) => count += 1,
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+    Ok(())
+}
+
+fn speak(tts: &KittenTTS, settings: &Settings, output: &Output, count: usize, text: &str) -> Result<()> {
+    if settings.phonemes {
+        let text = speakable_text(text, settings.input_format, settings.code_blocks);
+        eprintln!("Phonemes: {}", tts.phonemize(&text, Some(&settings.language))?);
+    }
+    let request = SynthesisRequest::new(text)
+        .voice(settings.voice.as_str())
+        .speed(settings.speed)
+        .language(settings.language.as_str())
+        .pitch(settings.pitch)
+        .input_format(settings.input_format)
+        .code_blocks(settings.code_blocks);
+    let audio = tts.synthesize(&request)?;
+    let bytes = settings.format.encode(&audio.samples, audio.sample_rate)?;
+
+    match output {
+        Output::Stdout => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        }
+        Output::Files { dir, keep } => {
+            let path = dir.join(format!("repl-{:02}.{}", count % (*keep).max(1) + 1, settings.format.extension()));
+            std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("{:.2}s of audio saved to {}", audio.duration_secs(), path.display());
+        }
+    }
+    Ok(())
+}
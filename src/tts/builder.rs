@@ -0,0 +1,248 @@
+use crate::models::{ModelFiles, ModelLocation, ModelSpec};
+use crate::onnx::{AccelerationProvider, SessionOptions};
+use crate::tts::phonemizer::{Phonemizer, PhonemizerBackend};
+use crate::tts::KittenTTS;
+use crate::utils::TrimOptions;
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// How much `KittenTTS` prints to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    /// Nothing
+    #[default]
+    Quiet,
+    /// Model loading and warnings
+    Info,
+    /// Also every loaded voice and the language, phonemes and tokens of each request
+    Debug,
+}
+
+/// What happens to the model output before it is returned
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcessing {
+    /// Trim leading and trailing silence; `None` returns the audio untrimmed
+    pub trim: Option<TrimOptions>,
+    /// Scale the audio so its loudest sample reaches this level
+    pub normalize_peak: Option<f32>,
+}
+
+impl Default for PostProcessing {
+    fn default() -> Self {
+        Self { trim: Some(TrimOptions::default()), normalize_peak: None }
+    }
+}
+
+/// Defaults applied to every request, set through `KittenTTSBuilder`
+#[derive(Debug, Clone)]
+pub(crate) struct Settings {
+    pub(crate) voice: Option<String>,
+    pub(crate) language: String,
+    pub(crate) speed: f32,
+    pub(crate) post_processing: PostProcessing,
+    pub(crate) log_level: LogLevel,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            voice: None,
+            // English, matching the reference implementation
+            language: "en-us".to_string(),
+            speed: 1.0,
+            post_processing: PostProcessing::default(),
+            log_level: LogLevel::default(),
+        }
+    }
+}
+
+/// Configures and loads a `KittenTTS`; start with `KittenTTS::builder()`.
+///
+/// By default the default model is loaded from the shared cache on the CPU provider.
+#[derive(Default)]
+pub struct KittenTTSBuilder {
+    model: ModelSpec,
+    /// Applied to `model` when building, so it holds whichever order the calls come in
+    offline: Option<bool>,
+    location: ModelLocation,
+    files: Option<ModelFiles>,
+    provider: Option<AccelerationProvider>,
+    session_options: SessionOptions,
+    phonemizer: Option<Box<dyn Phonemizer>>,
+    settings: Settings,
+}
+
+impl KittenTTSBuilder {
+    /// Model variant to load (see `ModelSpec::resolve`)
+    pub fn model(mut self, model: ModelSpec) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Keep the model in a plain directory instead of the shared cache
+    pub fn model_dir(mut self, model_dir: impl Into<PathBuf>) -> Self {
+        self.location = ModelLocation::Dir(model_dir.into());
+        self
+    }
+
+    /// Use a HuggingFace-style cache other than `models::default_cache_dir()`
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.location = ModelLocation::Cache(cache_dir.into());
+        self
+    }
+
+    /// Load exactly these files, ignoring the model and location settings
+    pub fn model_files(mut self, files: ModelFiles) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    /// Never download; fail if the model files are missing
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = Some(offline);
+        self
+    }
+
+    pub fn provider(mut self, provider: AccelerationProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    pub fn session_options(mut self, options: SessionOptions) -> Self {
+        self.session_options = options;
+        self
+    }
+
+    /// Phonemizer to use instead of `PhonemizerBackend::default()`
+    pub fn phonemizer(mut self, phonemizer: Box<dyn Phonemizer>) -> Self {
+        self.phonemizer = Some(phonemizer);
+        self
+    }
+
+    /// Voice used by requests that don't name one
+    pub fn voice(mut self, voice: impl Into<String>) -> Self {
+        self.settings.voice = Some(voice.into());
+        self
+    }
+
+    /// Language used by requests that don't set one (`en-us` by default, `auto` to detect)
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.settings.language = language.into();
+        self
+    }
+
+    /// Speed used by requests that don't set one
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.settings.speed = speed;
+        self
+    }
+
+    pub fn post_processing(mut self, post_processing: PostProcessing) -> Self {
+        self.settings.post_processing = post_processing;
+        self
+    }
+
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.settings.log_level = log_level;
+        self
+    }
+
+    /// Load the model without an async runtime. Missing files are downloaded on a
+    /// private runtime (see `ModelLocation::fetch_blocking`).
+    pub fn build(self) -> Result<KittenTTS> {
+        let (files, config, model_id) = match self.files {
+            Some(files) => {
+                let (config, model_id) = KittenTTS::describe_files(&files)?;
+                (files, config, model_id)
+            }
+            None => {
+                let model = self.model_spec();
+                let (files, config) = self.location.fetch_blocking(&model)?;
+                (files, Some(config), model.id)
+            }
+        };
+        Self::finish(files, config, model_id, self.provider, self.session_options, self.phonemizer, self.settings)
+    }
+
+    /// Like `build`, but downloads on the caller's runtime
+    #[cfg(feature = "download")]
+    pub async fn build_async(self) -> Result<KittenTTS> {
+        let (files, config, model_id) = match self.files {
+            Some(files) => {
+                let (config, model_id) = KittenTTS::describe_files(&files)?;
+                (files, config, model_id)
+            }
+            None => {
+                let model = self.model_spec();
+                let (files, config) = self.location.fetch(&model).await?;
+                (files, Some(config), model.id)
+            }
+        };
+        Self::finish(files, config, model_id, self.provider, self.session_options, self.phonemizer, self.settings)
+    }
+
+    /// The model to fetch, with `offline` applied
+    fn model_spec(&self) -> ModelSpec {
+        match self.offline {
+            Some(offline) => self.model.clone().offline(offline),
+            None => self.model.clone(),
+        }
+    }
+
+    fn finish(
+        files: ModelFiles,
+        config: Option<crate::models::ModelConfig>,
+        model_id: String,
+        provider: Option<AccelerationProvider>,
+        options: SessionOptions,
+        phonemizer: Option<Box<dyn Phonemizer>>,
+        settings: Settings,
+    ) -> Result<KittenTTS> {
+        let phonemizer = match phonemizer {
+            Some(phonemizer) => phonemizer,
+            None => PhonemizerBackend::default().build()?,
+        };
+        let voice = settings.voice.clone();
+        let tts = KittenTTS::load_files(
+            &files,
+            config.as_ref(),
+            &model_id,
+            provider.unwrap_or(AccelerationProvider::Cpu),
+            &options,
+            phonemizer,
+            settings,
+        )?;
+        if let Some(voice) = voice {
+            anyhow::ensure!(
+                tts.available_voices().contains(&voice),
+                "Default voice '{}' not available. Available voices: {:?}",
+                voice,
+                tts.available_voices()
+            );
+        }
+        Ok(tts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_survives_a_later_model_call() {
+        let nano = || ModelSpec::resolve("nano-0.8").unwrap().offline(false);
+        let before = KittenTTSBuilder::default().offline(true).model(nano());
+        let after = KittenTTSBuilder::default().model(nano()).offline(true);
+        for builder in [before, after] {
+            let spec = builder.model_spec();
+            assert_eq!(spec.id, "nano-0.8");
+            assert!(spec.offline);
+        }
+    }
+
+    #[test]
+    fn model_keeps_its_own_offline_setting_without_offline_call() {
+        let spec = KittenTTSBuilder::default().model(ModelSpec::resolve("nano-0.8").unwrap().offline(true)).model_spec();
+        assert!(spec.offline);
+    }
+}
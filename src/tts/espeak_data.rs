@@ -0,0 +1,185 @@
+// espeak-ng for the statically linked (bundled) build
+// The data directory is embedded into the binary at build time and extracted to
+// the user cache on first use, so a single kittenx binary works on a clean host.
+// espeak-ng is initialized here with that directory instead of through espeak-rs,
+// which only takes it from an environment variable.
+
+use anyhow::{Context, Result};
+use espeak_rs_sys as sys;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ffi::{c_void, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// Environment variable naming the directory that contains `espeak-ng-data`
+pub const ESPEAK_DATA_ENV: &str = "PIPER_ESPEAKNG_DATA_DIRECTORY";
+
+const DATA_DIR_NAME: &str = "espeak-ng-data";
+const EMBEDDED_ARCHIVE: &[u8] = include_bytes!(env!("KITTENX_ESPEAK_DATA_ARCHIVE"));
+const EMBEDDED_ID: &str = env!("KITTENX_ESPEAK_DATA_ID");
+
+/// Directory espeak-ng was initialized with (`None` for its built-in path), or why it failed
+static INITIALIZED: OnceLock<Result<Option<PathBuf>, String>> = OnceLock::new();
+/// espeak-ng keeps the current voice and its text position in globals
+static ESPEAK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    ESPEAK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Find espeak-ng-data and initialize espeak-ng with it, once per process. Returns the
+/// directory containing `espeak-ng-data`, or `None` if espeak-ng uses its built-in path.
+///
+/// Search order: `PIPER_ESPEAKNG_DATA_DIRECTORY`, an `espeak-ng-data` directory
+/// next to the executable or in the working directory, then the copy embedded
+/// in the binary (extracted to the cache directory).
+pub fn initialize() -> Result<Option<&'static Path>> {
+    let initialized = INITIALIZED.get_or_init(|| {
+        let data_dir = find_data_dir();
+        let path = data_dir
+            .as_ref()
+            .map(|dir| CString::new(dir.to_string_lossy().into_owned()))
+            .transpose()
+            .map_err(|e| format!("Invalid espeak-ng-data path: {}", e))?;
+        let _espeak = lock();
+        let sample_rate = unsafe {
+            sys::espeak_Initialize(
+                sys::espeak_AUDIO_OUTPUT_AUDIO_OUTPUT_RETRIEVAL,
+                0,
+                path.as_ref().map_or(std::ptr::null(), |path| path.as_ptr()),
+                sys::espeakINITIALIZE_DONT_EXIT as i32,
+            )
+        };
+        if sample_rate <= 0 {
+            return Err(format!(
+                "Failed to initialize espeak-ng (error {}). Set {} to the directory that contains {}",
+                sample_rate, ESPEAK_DATA_ENV, DATA_DIR_NAME
+            ));
+        }
+        Ok(data_dir)
+    });
+    match initialized {
+        Ok(data_dir) => Ok(data_dir.as_deref()),
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    }
+}
+
+/// Phonemize `text` as IPA without stress marks or language-switch flags, line by line
+pub fn text_to_phonemes(text: &str, language: &str) -> Result<String> {
+    lazy_static! {
+        static ref LANGUAGE_SWITCH: Regex = Regex::new(r"\([^)]*\)").unwrap();
+        static ref STRESS: Regex = Regex::new(r"[ˈˌ]").unwrap();
+    }
+
+    initialize()?;
+    let voice = CString::new(language)?;
+    let mut phonemes = String::new();
+    let _espeak = lock();
+    let status = unsafe { sys::espeak_SetVoiceByName(voice.as_ptr()) };
+    anyhow::ensure!(status == sys::espeak_ERROR_EE_OK, "espeak-ng has no voice for '{}'", language);
+
+    for line in text.lines() {
+        let line = CString::new(line)?;
+        let mut position = line.as_ptr() as *const c_void;
+        // espeak-ng advances `position` one clause at a time and clears it at the end
+        while !position.is_null() {
+            let clause = unsafe {
+                sys::espeak_TextToPhonemes(
+                    &mut position,
+                    sys::espeakCHARS_UTF8 as i32,
+                    sys::espeakINITIALIZE_PHONEME_IPA as i32,
+                )
+            };
+            if !clause.is_null() {
+                phonemes.push_str(&unsafe { CStr::from_ptr(clause) }.to_string_lossy());
+            }
+            // The clause's own punctuation isn't reported, so every clause ends a sentence
+            phonemes.push('.');
+        }
+    }
+
+    let phonemes = LANGUAGE_SWITCH.replace_all(&phonemes, "");
+    Ok(STRESS.replace_all(&phonemes, "").into_owned())
+}
+
+fn find_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(ESPEAK_DATA_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = find_local_data_dir() {
+        return Some(dir);
+    }
+    match extract_embedded_data() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Warning: failed to extract embedded espeak-ng-data: {}", e);
+            None
+        }
+    }
+}
+
+fn find_local_data_dir() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let cwd = std::env::current_dir().ok();
+
+    [exe_dir, cwd]
+        .into_iter()
+        .flatten()
+        .find(|dir| dir.join(DATA_DIR_NAME).join("phontab").exists())
+}
+
+/// Extract the embedded archive, returning the directory that contains `espeak-ng-data`
+fn extract_embedded_data() -> Result<Option<PathBuf>> {
+    if EMBEDDED_ARCHIVE.is_empty() {
+        return Ok(None);
+    }
+
+    let cache_root = dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("kittenx")
+        .join(format!("espeak-{}", EMBEDDED_ID));
+    let data_dir = cache_root.join(DATA_DIR_NAME);
+    if data_dir.join("phontab").exists() {
+        return Ok(Some(cache_root));
+    }
+
+    // Extract into a scratch directory first so a partial extraction is never used
+    let staging = cache_root.join(format!(".{}-{}", DATA_DIR_NAME, std::process::id()));
+    let _ = fs::remove_dir_all(&staging);
+    unpack(EMBEDDED_ARCHIVE, &staging)?;
+    if fs::rename(&staging, &data_dir).is_err() {
+        // Another process won the race; its copy is identical
+        let _ = fs::remove_dir_all(&staging);
+    }
+
+    Ok(Some(cache_root))
+}
+
+/// Archive format written by build.rs: repeated
+/// [u32 path length][path bytes][u64 data length][data bytes]
+fn unpack(mut archive: &[u8], dest: &Path) -> Result<()> {
+    fn take<'a>(archive: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        anyhow::ensure!(archive.len() >= len, "Embedded espeak-ng-data archive is truncated");
+        let (head, tail) = archive.split_at(len);
+        *archive = tail;
+        Ok(head)
+    }
+
+    while !archive.is_empty() {
+        let path_len = u32::from_le_bytes(take(&mut archive, 4)?.try_into()?) as usize;
+        let relative = std::str::from_utf8(take(&mut archive, path_len)?)?;
+        let data_len = u64::from_le_bytes(take(&mut archive, 8)?.try_into()?) as usize;
+        let data = take(&mut archive, data_len)?;
+
+        let path = dest.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, data).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+// ARPAbet to IPA conversion
+// The output follows espeak-ng's en-us conventions (long vowel marks, ɹ, ɚ)
+// since that is what the KittenTTS models were trained on.
+
+/// Stress level carried by an ARPAbet vowel (the trailing digit in CMUdict)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stress {
+    None,
+    Primary,
+    Secondary,
+}
+
+/// Split an ARPAbet symbol like `AH0` into its base phone and stress
+pub fn split_stress(symbol: &str) -> (&str, Option<Stress>) {
+    match symbol.as_bytes().last() {
+        Some(b'0') => (&symbol[..symbol.len() - 1], Some(Stress::None)),
+        Some(b'1') => (&symbol[..symbol.len() - 1], Some(Stress::Primary)),
+        Some(b'2') => (&symbol[..symbol.len() - 1], Some(Stress::Secondary)),
+        _ => (symbol, None),
+    }
+}
+
+pub fn is_vowel(base: &str) -> bool {
+    matches!(
+        base,
+        "AA" | "AE" | "AH" | "AO" | "AW" | "AY" | "EH" | "ER" | "EY" | "IH" | "IY" | "OW" | "OY"
+            | "UH" | "UW"
+    )
+}
+
+/// Map a single ARPAbet phone (without stress digit) to IPA
+fn phone_to_ipa(base: &str, stress: Stress, word_final: bool) -> Option<&'static str> {
+    let stressed = stress != Stress::None;
+    let ipa = match base {
+        "AA" => "ɑː",
+        "AE" => "æ",
+        "AH" => if stressed { "ʌ" } else { "ə" },
+        "AO" => "ɔː",
+        "AW" => "aʊ",
+        "AY" => "aɪ",
+        "EH" => "ɛ",
+        "ER" => if stressed { "ɜː" } else { "ɚ" },
+        "EY" => "eɪ",
+        "IH" => "ɪ",
+        "IY" => if !stressed && word_final { "i" } else { "iː" },
+        "OW" => "oʊ",
+        "OY" => "ɔɪ",
+        "UH" => "ʊ",
+        "UW" => "uː",
+        "B" => "b",
+        "CH" => "tʃ",
+        "D" => "d",
+        "DH" => "ð",
+        "F" => "f",
+        "G" => "ɡ",
+        "HH" => "h",
+        "JH" => "dʒ",
+        "K" => "k",
+        "L" => "l",
+        "M" => "m",
+        "N" => "n",
+        "NG" => "ŋ",
+        "P" => "p",
+        "R" => "ɹ",
+        "S" => "s",
+        "SH" => "ʃ",
+        "T" => "t",
+        "TH" => "θ",
+        "V" => "v",
+        "W" => "w",
+        "Y" => "j",
+        "Z" => "z",
+        "ZH" => "ʒ",
+        _ => return None,
+    };
+    Some(ipa)
+}
+
+/// Convert an ARPAbet pronunciation (e.g. `["HH", "AH0", "L", "OW1"]`) to IPA.
+///
+/// Stress marks are placed directly before the stressed vowel, and an
+/// intervocalic T before an unstressed vowel is realized as a flap, matching
+/// espeak-ng's American English output.
+pub fn to_ipa<S: AsRef<str>>(phones: &[S]) -> String {
+    let parsed: Vec<(&str, Stress)> = phones
+        .iter()
+        .map(|p| {
+            let (base, stress) = split_stress(p.as_ref());
+            (base, stress.unwrap_or(Stress::None))
+        })
+        .collect();
+
+    let mut out = String::new();
+    for (i, &(base, stress)) in parsed.iter().enumerate() {
+        let word_final = i + 1 == parsed.len();
+
+        if is_vowel(base) {
+            match stress {
+                Stress::Primary => out.push('ˈ'),
+                Stress::Secondary => out.push('ˌ'),
+                Stress::None => {}
+            }
+        }
+
+        // American flapping: vowel + T + unstressed vowel -> ɾ
+        if base == "T" && i > 0 && !word_final {
+            let prev_vowel = is_vowel(parsed[i - 1].0) || parsed[i - 1].0 == "R";
+            let (next, next_stress) = parsed[i + 1];
+            if prev_vowel && is_vowel(next) && next_stress == Stress::None {
+                out.push('ɾ');
+                continue;
+            }
+        }
+
+        if let Some(ipa) = phone_to_ipa(base, stress, word_final) {
+            out.push_str(ipa);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_stress_digits() {
+        assert_eq!(split_stress("AH0"), ("AH", Some(Stress::None)));
+        assert_eq!(split_stress("OW1"), ("OW", Some(Stress::Primary)));
+        assert_eq!(split_stress("EY2"), ("EY", Some(Stress::Secondary)));
+        assert_eq!(split_stress("TH"), ("TH", None));
+    }
+
+    #[test]
+    fn converts_to_espeak_style_ipa() {
+        assert_eq!(to_ipa(&["HH", "AH0", "L", "OW1"]), "həlˈoʊ");
+        assert_eq!(to_ipa(&["B", "AH1", "T", "ER0"]), "bˈʌɾɚ");
+        assert_eq!(to_ipa(&["HH", "AE1", "P", "IY0"]), "hˈæpi");
+        assert_eq!(to_ipa(&["N", "ER1", "S"]), "nˈɜːs");
+        assert_eq!(to_ipa(&["R", "IY2", "CH", "DH", "AH0", "NG", "Z"]), "ɹˌiːtʃðəŋz");
+    }
+
+    #[test]
+    fn flaps_only_between_a_vowel_and_an_unstressed_vowel() {
+        // "attack": the T before the stressed vowel stays a T
+        assert_eq!(to_ipa(&["AH0", "T", "AE1", "K"]), "ətˈæk");
+        // Word-final T is never flapped
+        assert_eq!(to_ipa(&["K", "AE1", "T"]), "kˈæt");
+    }
+}
@@ -0,0 +1,316 @@
+// Pure-Rust English grapheme-to-phoneme conversion
+// Used when espeak-ng is unavailable: CMUdict lookup with ARPAbet to IPA
+// conversion, plus letter-to-sound rules for words missing from the lexicon.
+// The built-in lexicon is a small core of about 280 function words and irregular
+// spellings, so most words go through the rules unless a full CMUdict is loaded.
+
+pub mod arpabet;
+pub mod rules;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Environment variable pointing at a full CMUdict file to load on top of the built-in lexicon
+pub const CMUDICT_ENV: &str = "KITTENX_CMUDICT";
+
+const CORE_LEXICON: &str = include_str!("lexicon.dict");
+
+pub struct G2p {
+    lexicon: HashMap<String, Vec<String>>,
+}
+
+impl G2p {
+    /// Create a converter with the built-in core lexicon, plus the dictionary
+    /// named by `KITTENX_CMUDICT` if that variable is set.
+    pub fn new() -> Self {
+        let mut g2p = Self::core();
+        if let Ok(path) = std::env::var(CMUDICT_ENV) {
+            if let Err(e) = g2p.load_cmudict(Path::new(&path)) {
+                eprintln!("Warning: failed to load {} from {}: {}", CMUDICT_ENV, path, e);
+            }
+        }
+        g2p
+    }
+
+    /// Create a converter with only the built-in core lexicon
+    pub fn core() -> Self {
+        let mut g2p = Self { lexicon: HashMap::new() };
+        g2p.add_cmudict_entries(CORE_LEXICON);
+        g2p
+    }
+
+    /// Merge a CMUdict-format file into the lexicon
+    pub fn load_cmudict(&mut self, path: &Path) -> Result<usize> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read CMUdict file {}", path.display()))?;
+        // cmudict-0.7b is Latin-1 encoded; lossy decoding only affects comments
+        let contents = String::from_utf8_lossy(&bytes);
+        Ok(self.add_cmudict_entries(&contents))
+    }
+
+    /// Parse CMUdict lines (`WORD  PH1 PH2 ...`) and add them, returning the number added.
+    /// Alternate pronunciations (`WORD(1)`) are ignored in favour of the primary one.
+    pub fn add_cmudict_entries(&mut self, contents: &str) -> usize {
+        let mut added = 0;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(";;;") || line.starts_with('#') {
+                continue;
+            }
+            // cmudict.dict (lowercase, single space) and cmudict-0.7b (uppercase, two spaces) both work
+            let line = line.split(" #").next().unwrap_or(line);
+            let mut parts = line.split_whitespace();
+            let word = match parts.next() {
+                Some(w) if !w.ends_with(')') => w.to_lowercase(),
+                _ => continue,
+            };
+            let phones: Vec<String> = parts.map(|p| p.to_uppercase()).collect();
+            if phones.is_empty() {
+                continue;
+            }
+            self.lexicon.insert(word, phones);
+            added += 1;
+        }
+        added
+    }
+
+    pub fn lexicon_size(&self) -> usize {
+        self.lexicon.len()
+    }
+
+    /// ARPAbet pronunciation of a single word, from the lexicon or the spelling rules.
+    /// Unknown acronyms ("FBI", "HTML") are spelled out letter by letter.
+    pub fn word_to_arpabet(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        if let Some(phones) = self.lexicon.get(&lower) {
+            return phones.clone();
+        }
+
+        // Possessives and contractions missing from the lexicon: "kitten's"
+        if let Some(stem) = word.strip_suffix("'s").or_else(|| word.strip_suffix("'S")) {
+            let mut phones = self.word_to_arpabet(stem);
+            let voiceless = phones
+                .last()
+                .map(|p| matches!(p.as_str(), "P" | "T" | "K" | "F" | "TH"))
+                .unwrap_or(false);
+            phones.push(if voiceless { "S" } else { "Z" }.to_string());
+            return phones;
+        }
+
+        if is_acronym(word) {
+            return spell_letters(word);
+        }
+        rules::spell_to_arpabet(&lower)
+    }
+
+    /// IPA pronunciation of a single word
+    pub fn word_to_ipa(&self, word: &str) -> String {
+        arpabet::to_ipa(&self.word_to_arpabet(word))
+    }
+
+    /// Convert running text to IPA, keeping punctuation like espeak's
+    /// preserve_punctuation mode so the model still sees clause boundaries.
+    pub fn phonemize(&self, text: &str) -> String {
+        lazy_static::lazy_static! {
+            static ref TOKEN_RE: Regex = Regex::new(r"[A-Za-z]+(?:'[A-Za-z]+)*|\d+(?:\.\d+)?|[^\sA-Za-z\d]").unwrap();
+        }
+
+        let mut out = String::new();
+        for m in TOKEN_RE.find_iter(text) {
+            let token = m.as_str();
+            let first = token.chars().next().unwrap_or(' ');
+
+            if first.is_ascii_alphabetic() {
+                push_word(&mut out, &self.word_to_ipa(token));
+            } else if first.is_ascii_digit() {
+                for word in number_to_words(token).split_whitespace() {
+                    push_word(&mut out, &self.word_to_ipa(word));
+                }
+            } else if is_kept_punctuation(first) {
+                // Attach punctuation to the previous word, as espeak does
+                out.push(first);
+            }
+        }
+        out
+    }
+}
+
+impl Default for G2p {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn push_word(out: &mut String, ipa: &str) {
+    if ipa.is_empty() {
+        return;
+    }
+    if !out.is_empty() {
+        out.push(' ');
+    }
+    out.push_str(ipa);
+}
+
+/// All-caps words that are short or have no vowel letters to sound out ("GPU", "HTML").
+/// Longer ones like "NASA" are read as words.
+fn is_acronym(word: &str) -> bool {
+    let has_vowel = word.chars().any(|c| matches!(c, 'A' | 'E' | 'I' | 'O' | 'U'));
+    word.len() >= 2 && word.chars().all(|c| c.is_ascii_uppercase()) && (word.len() <= 3 || !has_vowel)
+}
+
+/// ARPAbet names of the letters A to Z
+const LETTER_NAMES: [&str; 26] = [
+    "EY", "B IY", "S IY", "D IY", "IY", "EH F", "JH IY", "EY CH", "AY", "JH EY", "K EY", "EH L",
+    "EH M", "EH N", "OW", "P IY", "K Y UW", "AA R", "EH S", "T IY", "Y UW", "V IY",
+    "D AH B AH L Y UW", "EH K S", "W AY", "Z IY",
+];
+
+/// Spell a word letter by letter, with the main stress on the last letter as in "F B I"
+fn spell_letters(word: &str) -> Vec<String> {
+    let letters: Vec<u8> = word.bytes().filter(u8::is_ascii_alphabetic).map(|b| b.to_ascii_uppercase()).collect();
+    let mut phones = Vec::new();
+    for (i, letter) in letters.iter().enumerate() {
+        let stress = if i + 1 == letters.len() { "1" } else { "2" };
+        let mut stressed = false;
+        for phone in LETTER_NAMES[(letter - b'A') as usize].split(' ') {
+            // Only the first vowel of a letter name is stressed ("double-u")
+            if arpabet::is_vowel(phone) {
+                phones.push(format!("{}{}", phone, if stressed { "0" } else { stress }));
+                stressed = true;
+            } else {
+                phones.push(phone.to_string());
+            }
+        }
+    }
+    phones
+}
+
+fn is_kept_punctuation(c: char) -> bool {
+    matches!(c, ';' | ':' | ',' | '.' | '!' | '?' | '¡' | '¿' | '—' | '…' | '"' | '«' | '»' | '“' | '”')
+}
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Spell out a number like `42` or `3.14` in English words
+pub fn number_to_words(number: &str) -> String {
+    let (int_part, frac_part) = match number.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (number, None),
+    };
+
+    let mut words = match int_part.parse::<u64>() {
+        Ok(n) => integer_to_words(n),
+        // Too long to be a quantity: read it digit by digit
+        Err(_) => digits_to_words(int_part),
+    };
+
+    if let Some(frac) = frac_part.filter(|f| !f.is_empty()) {
+        words.push_str(" point ");
+        words.push_str(&digits_to_words(frac));
+    }
+    words
+}
+
+fn digits_to_words(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn integer_to_words(n: u64) -> String {
+    if n < 20 {
+        return ONES[n as usize].to_string();
+    }
+    if n < 100 {
+        let tens = TENS[(n / 10) as usize];
+        return match n % 10 {
+            0 => tens.to_string(),
+            ones => format!("{} {}", tens, ONES[ones as usize]),
+        };
+    }
+    if n < 1000 {
+        let hundreds = format!("{} hundred", ONES[(n / 100) as usize]);
+        return match n % 100 {
+            0 => hundreds,
+            rest => format!("{} {}", hundreds, integer_to_words(rest)),
+        };
+    }
+
+    const SCALES: [(u64, &str); 4] = [
+        (1_000_000_000_000, "trillion"),
+        (1_000_000_000, "billion"),
+        (1_000_000, "million"),
+        (1_000, "thousand"),
+    ];
+    for &(scale, name) in &SCALES {
+        if n >= scale {
+            let head = format!("{} {}", integer_to_words(n / scale), name);
+            return match n % scale {
+                0 => head,
+                rest => format!("{} {}", head, integer_to_words(rest)),
+            };
+        }
+    }
+    digits_to_words(&n.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_out_numbers() {
+        assert_eq!(number_to_words("0"), "zero");
+        assert_eq!(number_to_words("42"), "forty two");
+        assert_eq!(number_to_words("115"), "one hundred fifteen");
+        assert_eq!(number_to_words("2025"), "two thousand twenty five");
+        assert_eq!(number_to_words("3000000"), "three million");
+        assert_eq!(number_to_words("3.14"), "three point one four");
+        // Too large for a u64: read digit by digit
+        assert_eq!(
+            number_to_words("123456789012345678901"),
+            "one two three four five six seven eight nine zero one two three four five six seven eight nine zero one"
+        );
+    }
+
+    #[test]
+    fn spells_out_unknown_acronyms() {
+        let g2p = G2p::core();
+        assert_eq!(g2p.word_to_arpabet("FBI"), ["EH2", "F", "B", "IY2", "AY1"]);
+        assert_eq!(g2p.word_to_arpabet("WWW")[..7], ["D", "AH2", "B", "AH0", "L", "Y", "UW0"]);
+        assert_eq!(g2p.word_to_ipa("HTML"), "ˌeɪtʃtˌiːˌɛmˈɛl");
+        // Long enough to read as a word, and words in the lexicon keep their pronunciation
+        assert_eq!(g2p.word_to_arpabet("NASA"), rules::spell_to_arpabet("nasa"));
+        assert_eq!(g2p.word_to_arpabet("US"), ["AH1", "S"]);
+    }
+
+    #[test]
+    fn uses_the_lexicon_before_the_rules() {
+        let mut g2p = G2p::core();
+        assert_eq!(g2p.word_to_arpabet("Above"), ["AH0", "B", "AH1", "V"]);
+        assert_eq!(g2p.word_to_arpabet("blick"), rules::spell_to_arpabet("blick"));
+
+        // Alternate pronunciations are skipped
+        assert_eq!(g2p.add_cmudict_entries("BLICK  B L IH1 K\nBLICK(1)  B L EH1 K\n"), 1);
+        assert_eq!(g2p.word_to_arpabet("blick"), ["B", "L", "IH1", "K"]);
+        assert_eq!(g2p.word_to_arpabet("blick's"), ["B", "L", "IH1", "K", "S"]);
+    }
+
+    #[test]
+    fn keeps_punctuation_attached_to_words() {
+        let g2p = G2p::core();
+        assert_eq!(g2p.phonemize("Hello, 42 water!"), "həlˈoʊ, fˈɔːɹɾi tˈuː wˈɔːɾɚ!");
+    }
+}
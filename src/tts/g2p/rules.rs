@@ -0,0 +1,336 @@
+// Letter-to-sound rules for English words missing from the lexicon
+// This is a compact rule set in the spirit of the NRL rules: it gets regular
+// spellings right and produces something pronounceable for the rest.
+
+use super::arpabet::is_vowel;
+
+/// A phone produced by the rules, before stress assignment
+struct Phone {
+    symbol: &'static str,
+    /// Whether the vowel came from a lone vowel letter and may reduce to schwa
+    reducible: bool,
+}
+
+fn is_vowel_letter(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+fn is_consonant_letter(c: char) -> bool {
+    c.is_ascii_alphabetic() && !is_vowel_letter(c) && c != 'y'
+}
+
+/// Suffixes that pull primary stress onto the syllable right before them
+const STRESS_ATTRACTING_SUFFIXES: &[&str] = &["tion", "sion", "ic", "ical", "ity", "ian", "ious"];
+
+/// Prefixes that are usually unstressed in longer words
+const UNSTRESSED_PREFIXES: &[&str] = &["a", "be", "de", "re", "pre", "con", "com", "ex"];
+
+/// Convert a lowercase ASCII word into ARPAbet phones using spelling rules
+pub fn spell_to_arpabet(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+
+    let phones = apply_rules(&chars);
+    assign_stress(&chars, phones)
+}
+
+fn apply_rules(w: &[char]) -> Vec<Phone> {
+    let n = w.len();
+    let at = |i: usize| -> Option<char> { w.get(i).copied() };
+    let rest = |i: usize| -> String { w[i.min(n)..].iter().collect() };
+    let starts = |i: usize, s: &str| rest(i).starts_with(s);
+    let ends_here = |i: usize, s: &str| rest(i) == s;
+    let vowel_count = w.iter().filter(|&&c| is_vowel_letter(c)).count();
+
+    let mut out: Vec<Phone> = Vec::new();
+    let push = |out: &mut Vec<Phone>, symbol: &'static str| {
+        // Collapse doubled consonants (e.g. "ll", "ss")
+        if !is_vowel(symbol) && out.last().map(|p| p.symbol) == Some(symbol) {
+            return;
+        }
+        out.push(Phone { symbol, reducible: false });
+    };
+    let push_reducible = |out: &mut Vec<Phone>, symbol: &'static str| {
+        out.push(Phone { symbol, reducible: true });
+    };
+
+    let mut i = 0;
+    while i < n {
+        let c = w[i];
+        let next = at(i + 1);
+
+        // Multi-letter patterns first, longest first
+        if starts(i, "tion") {
+            push(&mut out, "SH");
+            push(&mut out, "AH");
+            push(&mut out, "N");
+            i += 4;
+            continue;
+        }
+        if starts(i, "sion") {
+            push(&mut out, if i > 0 && is_vowel_letter(w[i - 1]) { "ZH" } else { "SH" });
+            push(&mut out, "AH");
+            push(&mut out, "N");
+            i += 4;
+            continue;
+        }
+        if starts(i, "ture") && i > 0 {
+            push(&mut out, "CH");
+            push(&mut out, "ER");
+            i += 4;
+            continue;
+        }
+        if starts(i, "eigh") {
+            push(&mut out, "EY");
+            i += 4;
+            continue;
+        }
+        if starts(i, "igh") {
+            push(&mut out, "AY");
+            i += 3;
+            continue;
+        }
+        if starts(i, "tch") {
+            push(&mut out, "CH");
+            i += 3;
+            continue;
+        }
+        if starts(i, "ous") && i > 0 {
+            push(&mut out, "AH");
+            push(&mut out, "S");
+            i += 3;
+            continue;
+        }
+        if i > 0 && ends_here(i, "le") && is_consonant_letter(w[i - 1]) {
+            push(&mut out, "AH");
+            push(&mut out, "L");
+            i += 2;
+            continue;
+        }
+
+        match (c, next) {
+            ('c', Some('h')) => { push(&mut out, "CH"); i += 2; continue; }
+            ('s', Some('h')) => { push(&mut out, "SH"); i += 2; continue; }
+            ('t', Some('h')) => { push(&mut out, "TH"); i += 2; continue; }
+            ('p', Some('h')) => { push(&mut out, "F"); i += 2; continue; }
+            ('w', Some('h')) => { push(&mut out, "W"); i += 2; continue; }
+            ('c', Some('k')) => { push(&mut out, "K"); i += 2; continue; }
+            ('n', Some('g')) => { push(&mut out, "NG"); i += 2; continue; }
+            ('q', Some('u')) => { push(&mut out, "K"); push(&mut out, "W"); i += 2; continue; }
+            ('k', Some('n')) if i == 0 => { push(&mut out, "N"); i += 2; continue; }
+            ('w', Some('r')) if i == 0 => { push(&mut out, "R"); i += 2; continue; }
+            ('m', Some('b')) if i + 2 == n => { push(&mut out, "M"); i += 2; continue; }
+            ('g', Some('h')) => {
+                if i == 0 {
+                    push(&mut out, "G");
+                }
+                i += 2;
+                continue;
+            }
+            ('e', Some('e')) | ('e', Some('a')) => { push(&mut out, "IY"); i += 2; continue; }
+            ('o', Some('o')) => { push(&mut out, "UW"); i += 2; continue; }
+            ('o', Some('u')) => { push(&mut out, "AW"); i += 2; continue; }
+            ('o', Some('w')) => {
+                push(&mut out, if i + 2 == n { "OW" } else { "AW" });
+                i += 2;
+                continue;
+            }
+            ('o', Some('a')) => { push(&mut out, "OW"); i += 2; continue; }
+            ('o', Some('i')) | ('o', Some('y')) => { push(&mut out, "OY"); i += 2; continue; }
+            ('a', Some('i')) | ('a', Some('y')) => { push(&mut out, "EY"); i += 2; continue; }
+            ('a', Some('u')) | ('a', Some('w')) => { push(&mut out, "AO"); i += 2; continue; }
+            ('e', Some('i')) | ('e', Some('y')) => { push(&mut out, "EY"); i += 2; continue; }
+            ('e', Some('w')) | ('u', Some('e')) => { push(&mut out, "UW"); i += 2; continue; }
+            ('i', Some('e')) => {
+                push(&mut out, if i + 2 == n { "AY" } else { "IY" });
+                i += 2;
+                continue;
+            }
+            ('a', Some('r')) if !matches!(at(i + 2), Some(c) if is_vowel_letter(c)) => {
+                push(&mut out, "AA");
+                push(&mut out, "R");
+                i += 2;
+                continue;
+            }
+            ('o', Some('r')) if !matches!(at(i + 2), Some(c) if is_vowel_letter(c)) => {
+                push(&mut out, "AO");
+                push(&mut out, "R");
+                i += 2;
+                continue;
+            }
+            ('e', Some('r')) | ('i', Some('r')) | ('u', Some('r'))
+                if !matches!(at(i + 2), Some(c) if is_vowel_letter(c)) =>
+            {
+                push(&mut out, "ER");
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Silent final e (but not in words whose only vowel is that e)
+        if c == 'e' && i + 1 == n && vowel_count > 1 {
+            i += 1;
+            continue;
+        }
+        // Plural/past endings after a silent e: "makes", "named"
+        if c == 'e' && i + 2 == n && matches!(next, Some('s') | Some('d')) && vowel_count > 1
+            && i > 0 && is_consonant_letter(w[i - 1])
+            && !matches!(w[i - 1], 's' | 'z' | 'x' | 'c' | 'g' | 't' | 'd')
+        {
+            i += 1;
+            continue;
+        }
+
+        if is_vowel_letter(c) {
+            // Magic e: vowel + one consonant + final e (optionally followed by s/d)
+            let magic_e = matches!(next, Some(n1) if is_consonant_letter(n1))
+                && at(i + 2) == Some('e')
+                && (i + 3 == n || (i + 4 == n && matches!(at(i + 3), Some('s') | Some('d'))));
+            let open_syllable = i + 1 == n;
+            // "nation", "motion": a, o and u are long before -tion/-sion
+            let before_tion = c != 'e' && c != 'i' && (starts(i + 1, "tion") || starts(i + 1, "sion"));
+            if magic_e || before_tion || (open_syllable && vowel_count == 1) {
+                push(&mut out, match c {
+                    'a' => "EY",
+                    'e' => "IY",
+                    'i' => "AY",
+                    'o' => "OW",
+                    _ => "UW",
+                });
+            } else {
+                push_reducible(&mut out, match c {
+                    'a' => "AE",
+                    'e' => "EH",
+                    'i' => "IH",
+                    'o' => "AA",
+                    _ => "AH",
+                });
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            'y' => {
+                if i == 0 {
+                    push(&mut out, "Y");
+                } else if i + 1 == n {
+                    push(&mut out, if vowel_count == 0 { "AY" } else { "IY" });
+                } else if next.is_some_and(is_consonant_letter) {
+                    push_reducible(&mut out, "IH");
+                } else {
+                    push(&mut out, "Y");
+                }
+            }
+            'c' => push(&mut out, if matches!(next, Some('e' | 'i' | 'y')) { "S" } else { "K" }),
+            'g' => push(&mut out, if matches!(next, Some('e' | 'i' | 'y')) && i > 0 { "JH" } else { "G" }),
+            'x' => {
+                if i == 0 {
+                    push(&mut out, "Z");
+                } else {
+                    push(&mut out, "K");
+                    push(&mut out, "S");
+                }
+            }
+            's' => push(&mut out, if i > 0 && i + 1 == n && !matches!(w[i - 1], 'p' | 't' | 'k' | 'f' | 's') { "Z" } else { "S" }),
+            'j' => push(&mut out, "JH"),
+            'q' => push(&mut out, "K"),
+            'b' => push(&mut out, "B"),
+            'd' => push(&mut out, "D"),
+            'f' => push(&mut out, "F"),
+            'h' => push(&mut out, "HH"),
+            'k' => push(&mut out, "K"),
+            'l' => push(&mut out, "L"),
+            'm' => push(&mut out, "M"),
+            'n' => push(&mut out, "N"),
+            'p' => push(&mut out, "P"),
+            'r' => push(&mut out, "R"),
+            't' => push(&mut out, "T"),
+            'v' => push(&mut out, "V"),
+            'w' => push(&mut out, "W"),
+            'z' => push(&mut out, "Z"),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    out
+}
+
+fn assign_stress(word: &[char], phones: Vec<Phone>) -> Vec<String> {
+    let vowel_positions: Vec<usize> = phones
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| is_vowel(p.symbol))
+        .map(|(i, _)| i)
+        .collect();
+
+    if vowel_positions.is_empty() {
+        return phones.into_iter().map(|p| p.symbol.to_string()).collect();
+    }
+
+    let spelled: String = word.iter().collect();
+    let syllables = vowel_positions.len();
+
+    // Default: stress the first syllable, like most English words
+    let mut stressed = 0;
+    if syllables > 1 {
+        if let Some(suffix) = STRESS_ATTRACTING_SUFFIXES.iter().find(|s| spelled.ends_with(*s)) {
+            // The suffix contributes its own vowel(s); stress the one before them
+            let suffix_vowels = suffix.chars().filter(|&c| is_vowel_letter(c)).count().max(1);
+            let suffix_syllables = if suffix.starts_with("ti") || suffix.starts_with("si") {
+                1
+            } else {
+                suffix_vowels
+            };
+            stressed = syllables.saturating_sub(suffix_syllables + 1);
+        } else if syllables > 2
+            && UNSTRESSED_PREFIXES.iter().any(|p| spelled.starts_with(p) && spelled.len() > p.len() + 3)
+        {
+            stressed = 1;
+        }
+    }
+
+    phones
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if !is_vowel(p.symbol) {
+                return p.symbol.to_string();
+            }
+            let syllable = vowel_positions.iter().position(|&v| v == i).unwrap_or(0);
+            if syllable == stressed {
+                format!("{}1", p.symbol)
+            } else if p.reducible && syllables > 1 && p.symbol != "IH" {
+                "AH0".to_string()
+            } else {
+                format!("{}0", p.symbol)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_regular_spellings() {
+        assert_eq!(spell_to_arpabet("cat"), ["K", "AE1", "T"]);
+        assert_eq!(spell_to_arpabet("make"), ["M", "EY1", "K"]);
+        assert_eq!(spell_to_arpabet("ship"), ["SH", "IH1", "P"]);
+        assert_eq!(spell_to_arpabet("night"), ["N", "AY1", "T"]);
+    }
+
+    #[test]
+    fn stresses_the_syllable_before_tion() {
+        assert_eq!(spell_to_arpabet("nation"), ["N", "EY1", "SH", "AH0", "N"]);
+    }
+}
@@ -1,127 +1,322 @@
-use crate::onnx::{KittenOnnx, AccelerationProvider};
-use crate::tts::{phonemizer, text_cleaner::TextCleaner};
-use crate::utils::{download_file, save_wav};
+use crate::models::{ModelConfig, ModelFiles, ModelLocation, ModelSpec};
+use crate::onnx::{AccelerationProvider, BatchItem, InferenceBackend, ModelOutputs, ModelSignature, ProviderStatus, SessionOptions};
+#[cfg(feature = "tract")]
+use crate::onnx::TractOnnx;
+use crate::tts::builder::{KittenTTSBuilder, LogLevel, Settings};
+use crate::tts::request::{SynthesisRequest, SynthesizedAudio};
+use crate::tts::markup::{self, InputFormat};
+use crate::tts::{phonemizer::{self, Phonemizer, PhonemizerBackend}, text_cleaner::TextCleaner, voices};
+use crate::utils::resample_by;
 use anyhow::{Context, Result};
-use ndarray::Array1;
-use ndarray_npy::NpzReader;
+use ndarray::Array2;
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "tract")]
+use std::io::Cursor;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+
+/// Voice used when neither the request nor the builder names one, if the model has it
+pub const DEFAULT_VOICE: &str = "expr-voice-5-m";
 
 pub struct KittenTTS {
-    model: Arc<Mutex<KittenOnnx>>,
-    voices: HashMap<String, Array1<f32>>,
+    model: Arc<dyn InferenceBackend>,
+    voices: HashMap<String, Array2<f32>>,
+    voice_aliases: HashMap<String, String>,
     text_cleaner: TextCleaner,
     available_voices: Vec<String>,
     sample_rate: u32,
+    phonemizer: Box<dyn Phonemizer>,
+    provider_status: ProviderStatus,
+    model_id: String,
+    model_files: ModelFiles,
+    settings: Settings,
 }
 
 impl KittenTTS {
+    /// Configure model source, provider, defaults, post-processing and logging in one place
+    pub fn builder() -> KittenTTSBuilder {
+        KittenTTSBuilder::default()
+    }
+
+    #[cfg(feature = "download")]
     pub async fn new(model_dir: &Path) -> Result<Self> {
         Self::with_provider(model_dir, AccelerationProvider::Cpu).await
     }
 
+    #[cfg(feature = "download")]
     pub async fn with_provider(model_dir: &Path, provider: AccelerationProvider) -> Result<Self> {
-        // Ensure model directory exists
-        tokio::fs::create_dir_all(model_dir).await?;
-
-        let config_path = model_dir.join("config.json");
-        let model_path = model_dir.join("kitten_tts_nano_v0_1.onnx");
-        let voices_path = model_dir.join("voices.npz");
-
-        // Download files if they don't exist
-        if !config_path.exists() {
-            download_file(
-                "https://huggingface.co/KittenML/kitten-tts-nano-0.1/resolve/main/config.json",
-                &config_path,
-            ).await?;
-        }
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::with_phonemizer(model_dir, provider, phonemizer).await
+    }
 
-        if !model_path.exists() {
-            download_file(
-                "https://huggingface.co/KittenML/kitten-tts-nano-0.1/resolve/main/kitten_tts_nano_v0_1.onnx",
-                &model_path,
-            ).await?;
-        }
+    /// Load the model with explicit ONNX Runtime session options
+    #[cfg(feature = "download")]
+    pub async fn with_options(
+        model_dir: &Path,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        Self::with_model(model_dir, &ModelSpec::default(), provider, options).await
+    }
+
+    /// Load a specific model variant (see `models::REGISTRY`), downloading it into
+    /// its own subdirectory of `model_dir` if needed
+    #[cfg(feature = "download")]
+    pub async fn with_model(
+        model_dir: &Path,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::load(&ModelLocation::Dir(model_dir.to_path_buf()), model, provider, &options, phonemizer).await
+    }
+
+    /// Load a model from the shared cache (see `models::default_cache_dir`), fetching
+    /// the pinned revision through the HuggingFace Hub (or `HF_ENDPOINT`) if needed
+    #[cfg(feature = "download")]
+    pub async fn from_cache(
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::load(&ModelLocation::default(), model, provider, &options, phonemizer).await
+    }
+
+    /// Load the model with a custom phonemizer instead of the built-in backends
+    #[cfg(feature = "download")]
+    pub async fn with_phonemizer(
+        model_dir: &Path,
+        provider: AccelerationProvider,
+        phonemizer: Box<dyn Phonemizer>,
+    ) -> Result<Self> {
+        Self::load(
+            &ModelLocation::Dir(model_dir.to_path_buf()),
+            &ModelSpec::default(),
+            provider,
+            &SessionOptions::default(),
+            phonemizer,
+        )
+        .await
+    }
+
+    /// Blocking counterpart of `new` for synchronous applications; see `with_model_blocking`
+    pub fn new_blocking(model_dir: &Path) -> Result<Self> {
+        Self::with_provider_blocking(model_dir, AccelerationProvider::Cpu)
+    }
+
+    pub fn with_provider_blocking(model_dir: &Path, provider: AccelerationProvider) -> Result<Self> {
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::with_phonemizer_blocking(model_dir, provider, phonemizer)
+    }
+
+    pub fn with_options_blocking(
+        model_dir: &Path,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        Self::with_model_blocking(model_dir, &ModelSpec::default(), provider, options)
+    }
+
+    /// Load a model variant from `model_dir` without an async runtime. Files that are
+    /// already present are used directly; missing ones are downloaded on a private
+    /// runtime (so don't call this from async code), or reported as an error when
+    /// the `download` feature is off.
+    pub fn with_model_blocking(
+        model_dir: &Path,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::load_blocking(&ModelLocation::Dir(model_dir.to_path_buf()), model, provider, &options, phonemizer)
+    }
+
+    /// Blocking counterpart of `from_cache`, with the same rules as `with_model_blocking`
+    pub fn from_cache_blocking(
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::load_blocking(&ModelLocation::default(), model, provider, &options, phonemizer)
+    }
+
+    pub fn with_phonemizer_blocking(
+        model_dir: &Path,
+        provider: AccelerationProvider,
+        phonemizer: Box<dyn Phonemizer>,
+    ) -> Result<Self> {
+        Self::load_blocking(
+            &ModelLocation::Dir(model_dir.to_path_buf()),
+            &ModelSpec::default(),
+            provider,
+            &SessionOptions::default(),
+            phonemizer,
+        )
+    }
 
-        if !voices_path.exists() {
-            download_file(
-                "https://huggingface.co/KittenML/kitten-tts-nano-0.1/resolve/main/voices.npz",
-                &voices_path,
-            ).await?;
+    /// Load strictly from the given files, never touching the network.
+    /// Without a `config.json` the model is assumed to run at 24 kHz with no voice aliases.
+    pub fn from_files(
+        files: &ModelFiles,
+        provider: AccelerationProvider,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let (config, model_id) = Self::describe_files(files)?;
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::load_files(files, config.as_ref(), &model_id, provider, &options, phonemizer, Settings::default())
+    }
+
+    /// Config and id (the model file's stem) of a model given as explicit files
+    pub(crate) fn describe_files(files: &ModelFiles) -> Result<(Option<ModelConfig>, String)> {
+        files.ensure_present()?;
+        let config = files.config.as_deref().map(ModelConfig::load).transpose()?;
+        let model_id = files.model.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok((config, model_id))
+    }
+
+    #[cfg(feature = "download")]
+    async fn load(
+        location: &ModelLocation,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: &SessionOptions,
+        phonemizer: Box<dyn Phonemizer>,
+    ) -> Result<Self> {
+        let (files, config) = location.fetch(model).await?;
+        Self::load_files(&files, Some(&config), &model.id, provider, options, phonemizer, Settings::default())
+    }
+
+    fn load_blocking(
+        location: &ModelLocation,
+        model: &ModelSpec,
+        provider: AccelerationProvider,
+        options: &SessionOptions,
+        phonemizer: Box<dyn Phonemizer>,
+    ) -> Result<Self> {
+        let (files, config) = location.fetch_blocking(model)?;
+        Self::load_files(&files, Some(&config), &model.id, provider, options, phonemizer, Settings::default())
+    }
+
+    pub(crate) fn load_files(
+        files: &ModelFiles,
+        config: Option<&ModelConfig>,
+        model_id: &str,
+        provider: AccelerationProvider,
+        options: &SessionOptions,
+        phonemizer: Box<dyn Phonemizer>,
+        settings: Settings,
+    ) -> Result<Self> {
+        let log_level = settings.log_level;
+        if log_level >= LogLevel::Info {
+            eprintln!("Loading model {} ({})", model_id, files.model.display());
         }
 
-        // Load ONNX model with specified provider
-        let model = Arc::new(Mutex::new(KittenOnnx::with_provider(model_path.to_str().unwrap(), provider)?));
+        // Load ONNX model with the requested backend and provider
+        let options = SessionOptions { log_level, ..options.clone() };
+        let onnx = options.backend.unwrap_or_default().load(&files.model, provider, &options)?;
+        let voices = File::open(&files.voices).context("Failed to open voices file")?;
+        let voices = Self::load_voices(voices, log_level)?;
+        Self::from_parts(onnx, voices, config, model_id, files.clone(), phonemizer, settings)
+    }
+
+    /// Load a model from the bytes of its `.onnx` file and voices NPZ, plus the text of its
+    /// `config.json` if there is one. It runs on tract and reads nothing from disk, so this
+    /// also works on wasm32; `model_files` is left empty.
+    #[cfg(feature = "tract")]
+    pub fn from_bytes(model: &[u8], voices: &[u8], config: Option<&str>) -> Result<Self> {
+        let config = config.map(ModelConfig::parse).transpose()?;
+        let onnx = Arc::new(TractOnnx::from_bytes(model)?);
+        let settings = Settings::default();
+        let voices = Self::load_voices(Cursor::new(voices), settings.log_level)?;
+        let phonemizer = PhonemizerBackend::default().build()?;
+        Self::from_parts(onnx, voices, config.as_ref(), "", ModelFiles::new("", ""), phonemizer, settings)
+    }
+
+    fn from_parts(
+        onnx: Arc<dyn InferenceBackend>,
+        voices: HashMap<String, Array2<f32>>,
+        config: Option<&ModelConfig>,
+        model_id: &str,
+        model_files: ModelFiles,
+        phonemizer: Box<dyn Phonemizer>,
+        settings: Settings,
+    ) -> Result<Self> {
+        let log_level = settings.log_level;
+        let provider_status = onnx.provider_status().clone();
 
-        // Load voices
-        let voices = Self::load_voices(&voices_path)?;
+        // default_voice falls back to the first voice, so there must be one
+        anyhow::ensure!(!voices.is_empty(), "The model has no usable voices");
 
-        let available_voices = vec![
-            "expr-voice-2-m".to_string(),
-            "expr-voice-2-f".to_string(),
-            "expr-voice-3-m".to_string(),
-            "expr-voice-3-f".to_string(),
-            "expr-voice-4-m".to_string(),
-            "expr-voice-4-f".to_string(),
-            "expr-voice-5-m".to_string(),
-            "expr-voice-5-f".to_string(),
-        ];
+        // Make sure the voices fit the model's style input
+        for (name, styles) in &voices {
+            onnx.signature().check_style(name, styles.ncols())?;
+        }
+
+        let mut available_voices: Vec<String> = voices.keys().cloned().collect();
+        available_voices.sort();
+        let mut voice_aliases = HashMap::new();
+        for (alias, target) in config.iter().flat_map(|c| &c.voice_aliases) {
+            if voices.contains_key(target) {
+                voice_aliases.insert(alias.clone(), target.clone());
+                available_voices.push(alias.clone());
+            } else if log_level >= LogLevel::Info {
+                eprintln!("Warning: voice alias {} points to unknown voice {}", alias, target);
+            }
+        }
 
         Ok(Self {
-            model,
+            model: onnx,
             voices,
+            voice_aliases,
             text_cleaner: TextCleaner::new(),
             available_voices,
-            sample_rate: 24000,
+            sample_rate: config.and_then(|c| c.sample_rate).unwrap_or(24000),
+            phonemizer,
+            provider_status,
+            model_id: model_id.to_string(),
+            model_files,
+            settings,
         })
     }
 
-    fn load_voices(voices_path: &Path) -> Result<HashMap<String, Array1<f32>>> {
-        let file = File::open(voices_path)
-            .context("Failed to open voices file")?;
-        
-        let mut npz = NpzReader::new(file)?;
-        let mut voices = HashMap::new();
-
-        // Try to load each voice
-        let voice_names = [
-            "expr-voice-2-m", "expr-voice-2-f", "expr-voice-3-m", "expr-voice-3-f",
-            "expr-voice-4-m", "expr-voice-4-f", "expr-voice-5-m", "expr-voice-5-f"
-        ];
-
-        for voice_name in &voice_names {
-            // First try loading as 2D array (which is the actual format in the NPZ file)
-            match npz.by_name::<ndarray::OwnedRepr<f32>, ndarray::Ix2>(voice_name) {
-                Ok(voice_array_2d) => {
-                    let len = voice_array_2d.len();
-                    let voice_array_1d = voice_array_2d.to_shape((len,))?.to_owned();
-                    println!("Loaded voice: {} with shape {:?}", voice_name, voice_array_1d.shape());
-                    voices.insert(voice_name.to_string(), voice_array_1d);
-                }
-                Err(_) => {
-                    // Fallback: try loading as 1D array
-                    match npz.by_name::<ndarray::OwnedRepr<f32>, ndarray::Ix1>(voice_name) {
-                        Ok(voice_array) => {
-                            println!("Loaded voice: {} with shape {:?}", voice_name, voice_array.shape());
-                            voices.insert(voice_name.to_string(), voice_array);
-                        }
-                        Err(_) => {
-                            println!("Warning: Voice {} not found in NPZ file, using dummy", voice_name);
-                            // Create a dummy voice embedding as fallback
-                            voices.insert(voice_name.to_string(), Array1::zeros(256));
-                        }
-                    }
-                }
+    /// Load every voice in the NPZ archive as a `[rows, style_dim]` matrix
+    fn load_voices(reader: impl Read + Seek, log_level: LogLevel) -> Result<HashMap<String, Array2<f32>>> {
+        let archive = voices::read_voices(reader)?;
+
+        if log_level >= LogLevel::Info {
+            for (voice_name, e) in &archive.skipped {
+                eprintln!("Warning: skipping voice {}: {}", voice_name, e);
             }
         }
+        Ok(archive
+            .voices
+            .into_iter()
+            .inspect(|(voice_name, voice_array)| {
+                if log_level >= LogLevel::Debug {
+                    eprintln!("Loaded voice: {} with shape {:?}", voice_name, voice_array.shape());
+                }
+            })
+            .collect())
+    }
 
-        if voices.is_empty() {
-            anyhow::bail!("No voices could be loaded from the NPZ file");
-        }
+    /// Identifier of the loaded model variant
+    pub fn model_id(&self) -> &str {
+        &self.model_id
+    }
 
-        Ok(voices)
+    /// How the model's ONNX inputs and outputs are used
+    pub fn signature(&self) -> &ModelSignature {
+        self.model.signature()
+    }
+
+    /// Paths of the files the model was loaded from (empty after `from_bytes`)
+    pub fn model_files(&self) -> &ModelFiles {
+        &self.model_files
     }
 
     pub fn available_voices(&self) -> &[String] {
@@ -132,23 +327,153 @@ impl KittenTTS {
         self.sample_rate
     }
 
+    /// The execution provider the model actually runs on, and why if it differs from the requested one
+    pub fn provider_status(&self) -> &ProviderStatus {
+        &self.provider_status
+    }
+
+    pub fn set_phonemizer(&mut self, phonemizer: Box<dyn Phonemizer>) {
+        self.phonemizer = phonemizer;
+    }
+
+    /// Voice used by requests that don't name one: the builder's, else `DEFAULT_VOICE`
+    /// if the model has it, else the first available voice
+    pub fn default_voice(&self) -> &str {
+        if let Some(voice) = &self.settings.voice {
+            return voice;
+        }
+        if self.available_voices.iter().any(|v| v == DEFAULT_VOICE) {
+            DEFAULT_VOICE
+        } else {
+            &self.available_voices[0]
+        }
+    }
+
     pub fn generate(&self, text: &str, voice: &str, speed: f32) -> Result<Vec<f32>> {
-        if !self.available_voices.contains(&voice.to_string()) {
-            anyhow::bail!("Voice '{}' not available. Available voices: {:?}", voice, self.available_voices);
+        let request = SynthesisRequest::new(text).voice(voice).speed(speed);
+        Ok(self.synthesize(&request)?.samples)
+    }
+
+    /// Synthesize with per-call options, falling back to the builder's defaults
+    pub fn synthesize(&self, request: &SynthesisRequest) -> Result<SynthesizedAudio> {
+        if request.input_format != InputFormat::Text {
+            return self.synthesize_blocks(request);
+        }
+        let voice = request.voice.as_deref().unwrap_or_else(|| self.default_voice());
+        let speed = request.speed.unwrap_or(self.settings.speed);
+        anyhow::ensure!(speed.is_finite() && speed > 0.0, "Speed must be positive, got {}", speed);
+        anyhow::ensure!(
+            (-12.0..=12.0).contains(&request.pitch),
+            "Pitch must be between -12 and 12 semitones, got {}",
+            request.pitch
+        );
+        let language = self.resolve_language(request.language.as_deref(), &request.text);
+
+        // Shift pitch by rendering slower by the pitch factor and resampling back to the
+        // original duration, which keeps the model's prosody intact
+        let pitch_factor = 2f32.powf(request.pitch / 12.0);
+        let tokens = self.text_to_tokens(&request.text, &language)?;
+        let style = self.voice_embedding(voice, &request.text)?;
+        let output = self.model.infer(vec![tokens], style, speed / pitch_factor)
+            .context("ONNX inference failed")?;
+        let audio: Vec<f32> = output.iter().copied().collect();
+
+        let mut samples = self.post_process(&audio);
+        let sample_rate = request.output_sample_rate.unwrap_or(self.sample_rate);
+        let ratio = f64::from(sample_rate) / f64::from(self.sample_rate) / f64::from(pitch_factor);
+        samples = resample_by(&samples, ratio);
+        if request.volume != 1.0 {
+            samples.iter_mut().for_each(|s| *s *= request.volume);
+        }
+        Ok(SynthesizedAudio { samples, sample_rate })
+    }
+
+    /// Phonemes the model is fed for `text`. `language` falls back to the builder's
+    /// default, and `auto` detects it from the text.
+    pub fn phonemize(&self, text: &str, language: Option<&str>) -> Result<String> {
+        let language = self.resolve_language(language, text);
+        phonemizer::phonemize_with(self.phonemizer.as_ref(), text, &language)
+    }
+
+    /// Speak each block of marked-up text separately, joined by the block's pause
+    fn synthesize_blocks(&self, request: &SynthesisRequest) -> Result<SynthesizedAudio> {
+        let mut block_request = request.clone().input_format(InputFormat::Text);
+        let sample_rate = request.output_sample_rate.unwrap_or(self.sample_rate);
+        let mut samples = Vec::new();
+        let blocks = markup::speech_blocks(&request.text, request.input_format, request.code_blocks);
+        for (i, block) in blocks.iter().enumerate() {
+            block_request.text.clone_from(&block.text);
+            samples.extend(self.synthesize(&block_request)?.samples);
+            if i + 1 < blocks.len() {
+                samples.resize(samples.len() + (block.pause_secs * sample_rate as f32) as usize, 0.0);
+            }
+        }
+        Ok(SynthesizedAudio { samples, sample_rate })
+    }
+
+    /// Run the model without trimming, returning the raw waveform together with the
+    /// predicted token durations and any other outputs the model exports
+    pub fn generate_outputs(&self, text: &str, voice: &str, speed: f32) -> Result<ModelOutputs> {
+        let tokens = self.text_to_tokens(text, &self.resolve_language(None, text))?;
+        let style = self.voice_embedding(voice, text)?;
+        self.model.infer_outputs(vec![tokens], style, speed)
+            .context("ONNX inference failed")
+    }
+
+    /// Synthesize several `(text, voice, speed)` utterances. Utterances with the same number
+    /// of tokens share a model run (see `KittenOnnx::infer_batch`); the rest run separately.
+    /// Returns one waveform per input, in order.
+    pub fn generate_batch(&self, requests: &[(&str, &str, f32)]) -> Result<Vec<Vec<f32>>> {
+        let items = requests
+            .iter()
+            .map(|&(text, voice, speed)| {
+                Ok(BatchItem {
+                    input_ids: self.text_to_tokens(text, &self.resolve_language(None, text))?,
+                    style: self.voice_embedding(voice, text)?,
+                    speed,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let outputs = self.model.infer_batch(&items)
+            .context("ONNX batch inference failed")?;
+
+        Ok(outputs.iter().map(|audio| self.post_process(audio)).collect())
+    }
+
+    /// Style vector for a voice and text
+    fn voice_embedding(&self, voice: &str, text: &str) -> Result<Vec<f32>> {
+        let name = self.voice_aliases.get(voice).map(String::as_str).unwrap_or(voice);
+        let styles = self.voices.get(name).ok_or_else(|| {
+            anyhow::anyhow!("Voice '{}' not available. Available voices: {:?}", voice, self.available_voices)
+        })?;
+
+        Ok(voices::style_for_text(styles, text))
+    }
+
+    /// The requested language, else the default one; `auto` is detected from the text
+    fn resolve_language(&self, requested: Option<&str>, text: &str) -> String {
+        let language = requested.unwrap_or(&self.settings.language);
+        if language.eq_ignore_ascii_case("auto") {
+            phonemizer::detect_language(text).unwrap_or_else(|| "en-us".to_string())
+        } else {
+            language.to_string()
         }
+    }
 
-        // Use English as default language (matching Python implementation)
-        let language = "en-us";
-        println!("Using language: {}", language);
+    fn text_to_tokens(&self, text: &str, language: &str) -> Result<Vec<i64>> {
+        let debug = self.settings.log_level >= LogLevel::Debug;
+        if debug {
+            eprintln!("Using language: {}", language);
+        }
 
-        // Convert text to phonemes using espeak backend with preserve_punctuation=True, with_stress=True
-        let phonemes = phonemizer::text_to_phonemes_simple(text, language)
-            .unwrap_or_else(|_| {
-                // Fallback to basic tokenization
-                phonemizer::basic_english_tokenize(text).join(" ")
-            });
+        // Convert text to phonemes using the configured phonemizer (espeak by default)
+        let phonemes = phonemizer::phonemize_with(self.phonemizer.as_ref(), text, language)
+            .context("Phonemization failed")?;
 
-        println!("Phonemes: {}", phonemes);
+        if debug {
+            eprintln!("Phonemes: {}", phonemes);
+        }
 
         // Convert phonemes to tokens (matching Python's approach)
         let mut tokens = self.text_cleaner.clean(&phonemes);
@@ -157,44 +482,41 @@ impl KittenTTS {
         tokens.insert(0, 0);
         tokens.push(0);
 
-        println!("Tokens: {:?}", tokens);
+        if debug {
+            eprintln!("Tokens: {:?}", tokens);
+        }
 
-        // Get voice embedding
-        let voice_embedding = self.voices.get(voice)
-            .ok_or_else(|| anyhow::anyhow!("Voice not found: {}", voice))?;
+        Ok(tokens)
+    }
 
-        // Run inference
-        let input_ids = vec![tokens];
-        let style = voice_embedding.to_vec();
-        
-        let output = {
-            let mut model = self.model.lock().unwrap();
-            model.infer(input_ids, style, speed)
-                .context("ONNX inference failed")?
+    /// Silence trimming and peak normalization, at the model's sample rate
+    fn post_process(&self, audio: &[f32]) -> Vec<f32> {
+        let post_processing = &self.settings.post_processing;
+        let mut audio = match &post_processing.trim {
+            // RMS-based trimming is safer than fixed cropping
+            Some(trim) => trim.apply(audio, self.sample_rate),
+            None => audio.to_vec(),
         };
-
-        // Convert output to Vec<f32>
-        let audio_data: Vec<f32> = output.iter().cloned().collect();
-
-        // Trim leading/trailing silence using RMS-based thresholding (safer than fixed cropping)
-        // Defaults chosen to avoid cutting off final words, while removing obvious head/tail silence
-        let trimmed = crate::utils::audio::trim_silence(
-            &audio_data,
-            self.sample_rate,
-            40.0,  // top_db relative to max RMS
-            20.0,  // frame_ms
-            10.0,  // hop_ms
-            150.0, // min_silence_ms
-            80.0,  // end_padding_ms to preserve last phonemes
-        );
-
-        Ok(trimmed)
+        if let Some(target) = post_processing.normalize_peak {
+            let peak = audio.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+            if peak > 0.0 {
+                let gain = target / peak;
+                audio.iter_mut().for_each(|s| *s *= gain);
+            }
+        }
+        audio
     }
 
     pub fn generate_to_file(&self, text: &str, voice: &str, speed: f32, output_path: &Path) -> Result<()> {
-        let audio = self.generate(text, voice, speed)?;
-        save_wav(&audio, self.sample_rate, output_path)?;
-        println!("Audio saved to {}", output_path.display());
+        let request = SynthesisRequest::new(text).voice(voice).speed(speed);
+        self.synthesize_to_file(&request, output_path)
+    }
+
+    pub fn synthesize_to_file(&self, request: &SynthesisRequest, output_path: &Path) -> Result<()> {
+        self.synthesize(request)?.save_wav(output_path)?;
+        if self.settings.log_level >= LogLevel::Info {
+            eprintln!("Audio saved to {}", output_path.display());
+        }
         Ok(())
     }
 }
\ No newline at end of file
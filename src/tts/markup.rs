@@ -0,0 +1,380 @@
+// Markdown and HTML input cleaning
+// Turns marked-up text into speakable blocks: link text without URLs, code blocks skipped
+// or announced, list items as separate sentences and headings as longer breaks.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// How the input text is marked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum InputFormat {
+    /// Plain text, spoken as is
+    #[default]
+    Text,
+    /// Markdown (CommonMark and GitHub extensions such as tables)
+    Markdown,
+    /// HTML; scripts and styles are ignored
+    Html,
+}
+
+/// What to do with code blocks, which rarely make sense read aloud
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CodeBlocks {
+    /// Say "Code block." in their place
+    #[default]
+    Announce,
+    /// Leave them out
+    Skip,
+}
+
+/// Silence after a list item, table row or line break, in seconds
+pub const ITEM_PAUSE: f32 = 0.25;
+/// Silence after a paragraph
+pub const PARAGRAPH_PAUSE: f32 = 0.5;
+/// Silence before and after a heading
+pub const HEADING_PAUSE: f32 = 0.9;
+
+/// A run of speakable text and the silence to leave after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechBlock {
+    pub text: String,
+    pub pause_secs: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Paragraph,
+    Item,
+    Heading,
+}
+
+/// Blocks collected while converting, with the pause rules applied as they are pushed
+#[derive(Default)]
+struct Blocks {
+    blocks: Vec<SpeechBlock>,
+    /// Decode HTML entities once the markup is gone, so `&lt;` is read rather than stripped as a tag
+    decode_entities: bool,
+}
+
+impl Blocks {
+    fn push(&mut self, text: &str, kind: BlockKind) {
+        let mut text = clean_inline(text, self.decode_entities);
+        if text.is_empty() {
+            return;
+        }
+        // Headings and list items often lack final punctuation; without it the model
+        // runs them into the next sentence
+        if kind != BlockKind::Paragraph && !text.ends_with(['.', '!', '?', ':', ';', '…']) {
+            text.push('.');
+        }
+        let pause_secs = match kind {
+            BlockKind::Paragraph => PARAGRAPH_PAUSE,
+            BlockKind::Item => ITEM_PAUSE,
+            BlockKind::Heading => HEADING_PAUSE,
+        };
+        if kind == BlockKind::Heading {
+            if let Some(previous) = self.blocks.last_mut() {
+                previous.pause_secs = previous.pause_secs.max(HEADING_PAUSE);
+            }
+        }
+        self.blocks.push(SpeechBlock { text, pause_secs });
+    }
+
+    fn code_block(&mut self, language: &str, code_blocks: CodeBlocks) {
+        if code_blocks == CodeBlocks::Announce {
+            let language = language.trim();
+            if language.is_empty() {
+                self.push("Code block.", BlockKind::Paragraph);
+            } else {
+                self.push(&format!("{} code block.", language), BlockKind::Paragraph);
+            }
+        }
+    }
+}
+
+/// Convert `input` into speakable blocks. Plain text becomes a single block.
+pub fn speech_blocks(input: &str, format: InputFormat, code_blocks: CodeBlocks) -> Vec<SpeechBlock> {
+    match format {
+        InputFormat::Text => {
+            let text = input.trim();
+            if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![SpeechBlock { text: text.to_string(), pause_secs: 0.0 }]
+            }
+        }
+        InputFormat::Markdown => markdown_blocks(input, code_blocks),
+        InputFormat::Html => html_blocks(input, code_blocks),
+    }
+}
+
+/// Convert `input` into speakable text, one block per line
+pub fn speakable_text(input: &str, format: InputFormat, code_blocks: CodeBlocks) -> String {
+    speech_blocks(input, format, code_blocks)
+        .into_iter()
+        .map(|block| block.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn markdown_blocks(input: &str, code_blocks: CodeBlocks) -> Vec<SpeechBlock> {
+    lazy_static! {
+        static ref FENCE: Regex = Regex::new(r"^\s{0,3}(`{3,}|~{3,})\s*([^`\s]*)").unwrap();
+        static ref HEADING: Regex = Regex::new(r"^\s{0,3}#{1,6}(?:\s+(.*?))?(?:\s+#+)?\s*$").unwrap();
+        static ref SETEXT: Regex = Regex::new(r"^\s{0,3}(=+|-+)\s*$").unwrap();
+        static ref RULE: Regex = Regex::new(r"^\s{0,3}([-*_])(?:\s*[-*_]){2,}\s*$").unwrap();
+        static ref LIST_ITEM: Regex = Regex::new(r"^\s*(?:[-*+]|\d{1,9}[.)])\s+(?:\[[ xX]\]\s+)?(.*)$").unwrap();
+        static ref QUOTE: Regex = Regex::new(r"^\s{0,3}>\s?").unwrap();
+        static ref TABLE_SEPARATOR: Regex = Regex::new(r"^\s*\|?\s*:?-{3,}:?\s*(\|\s*:?-{3,}:?\s*)*\|?\s*$").unwrap();
+        static ref LINK_DEFINITION: Regex = Regex::new(r"^\s{0,3}\[[^\]]+\]:\s*\S+").unwrap();
+    }
+
+    let mut blocks = Blocks::default();
+    // Lines of the paragraph or list item being collected
+    let mut lines: Vec<String> = Vec::new();
+    let mut kind = BlockKind::Paragraph;
+    // Closing fence of the code block we are in
+    let mut fence: Option<String> = None;
+    let mut in_list = false;
+    let mut in_indented_code = false;
+    let mut previous_blank = true;
+
+    let flush = |lines: &mut Vec<String>, kind: &mut BlockKind, blocks: &mut Blocks| {
+        if !lines.is_empty() {
+            blocks.push(&lines.join(" "), *kind);
+            lines.clear();
+        }
+        *kind = BlockKind::Paragraph;
+    };
+
+    for line in input.lines() {
+        if let Some(close) = &fence {
+            let trimmed = line.trim();
+            if trimmed.starts_with(close.as_str()) && trimmed.chars().all(|c| close.starts_with(c)) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(caps) = FENCE.captures(line) {
+            flush(&mut lines, &mut kind, &mut blocks);
+            fence = Some(caps[1].to_string());
+            blocks.code_block(&caps[2], code_blocks);
+            previous_blank = false;
+            continue;
+        }
+
+        let blank = line.trim().is_empty();
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        if in_indented_code && (indented || blank) {
+            previous_blank = blank;
+            continue;
+        }
+        in_indented_code = false;
+        // Indented code: four spaces after a blank line, outside of lists
+        if indented && previous_blank && !in_list {
+            flush(&mut lines, &mut kind, &mut blocks);
+            in_indented_code = true;
+            blocks.code_block("", code_blocks);
+            continue;
+        }
+        let after_blank = previous_blank;
+        previous_blank = blank;
+
+        if blank {
+            flush(&mut lines, &mut kind, &mut blocks);
+            continue;
+        }
+        if after_blank && !indented && !LIST_ITEM.is_match(line) {
+            in_list = false;
+        }
+        if LINK_DEFINITION.is_match(line) {
+            continue;
+        }
+        let line = QUOTE.replace(line, "");
+
+        if let Some(caps) = HEADING.captures(&line) {
+            flush(&mut lines, &mut kind, &mut blocks);
+            in_list = false;
+            blocks.push(caps.get(1).map_or("", |m| m.as_str()), BlockKind::Heading);
+            continue;
+        }
+        if SETEXT.is_match(&line) && !lines.is_empty() && kind == BlockKind::Paragraph {
+            // The paragraph so far was a heading underlined with === or ---
+            kind = BlockKind::Heading;
+            flush(&mut lines, &mut kind, &mut blocks);
+            continue;
+        }
+        if RULE.is_match(&line) || TABLE_SEPARATOR.is_match(&line) {
+            flush(&mut lines, &mut kind, &mut blocks);
+            continue;
+        }
+        if let Some(caps) = LIST_ITEM.captures(&line) {
+            flush(&mut lines, &mut kind, &mut blocks);
+            in_list = true;
+            kind = BlockKind::Item;
+            lines.push(caps[1].to_string());
+            continue;
+        }
+        if line.trim_start().starts_with('|') {
+            flush(&mut lines, &mut kind, &mut blocks);
+            let cells: Vec<&str> =
+                line.trim().trim_matches('|').split('|').map(str::trim).filter(|c| !c.is_empty()).collect();
+            blocks.push(&cells.join(", "), BlockKind::Item);
+            continue;
+        }
+        // Continuation of the current paragraph or list item
+        lines.push(line.trim().to_string());
+    }
+    flush(&mut lines, &mut kind, &mut blocks);
+    blocks.blocks
+}
+
+fn html_blocks(input: &str, code_blocks: CodeBlocks) -> Vec<SpeechBlock> {
+    lazy_static! {
+        static ref TAG: Regex = Regex::new(r"(?s)<!--.*?-->|<(/?)([A-Za-z][A-Za-z0-9]*)([^>]*)>").unwrap();
+        static ref ALT: Regex = Regex::new(r#"(?i)\balt\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    }
+
+    let mut blocks = Blocks { decode_entities: true, ..Blocks::default() };
+    let mut text = String::new();
+    let mut kind = BlockKind::Paragraph;
+    // Element whose contents are ignored (script, style, pre) until it closes
+    let mut skipping: Option<String> = None;
+    let mut last = 0;
+
+    for caps in TAG.captures_iter(input) {
+        let whole = caps.get(0).unwrap();
+        if skipping.is_none() {
+            text.push_str(&input[last..whole.start()]);
+        }
+        last = whole.end();
+        let Some(name) = caps.get(2) else { continue }; // a comment
+        let name = name.as_str().to_ascii_lowercase();
+        let closing = &caps[1] == "/";
+
+        if let Some(skipped) = &skipping {
+            if closing && name == *skipped {
+                skipping = None;
+            }
+            continue;
+        }
+
+        match name.as_str() {
+            "script" | "style" | "template" if !closing => skipping = Some(name),
+            "pre" if !closing => {
+                blocks.push(&text, kind);
+                text.clear();
+                kind = BlockKind::Paragraph;
+                blocks.code_block("", code_blocks);
+                skipping = Some(name);
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                blocks.push(&text, if closing { BlockKind::Heading } else { kind });
+                text.clear();
+                kind = if closing { BlockKind::Paragraph } else { BlockKind::Heading };
+            }
+            "li" | "dt" | "dd" | "tr" | "br" | "caption" | "figcaption" => {
+                blocks.push(&text, if name == "br" { BlockKind::Item } else { kind });
+                text.clear();
+                // A line break inside a list item leaves the rest of the item an item
+                if name != "br" {
+                    kind = if closing { BlockKind::Paragraph } else { BlockKind::Item };
+                }
+            }
+            "p" | "div" | "blockquote" | "section" | "article" | "header" | "footer" | "main" | "aside" | "nav"
+            | "ul" | "ol" | "dl" | "table" | "hr" | "body" | "html" | "title" => {
+                blocks.push(&text, kind);
+                text.clear();
+                kind = BlockKind::Paragraph;
+            }
+            "td" | "th" if !closing && !text.trim().is_empty() => text.push_str(", "),
+            "img" => {
+                if let Some(alt) = ALT.captures(&caps[3]) {
+                    let alt = alt.get(1).or_else(|| alt.get(2)).map_or("", |m| m.as_str());
+                    text.push(' ');
+                    text.push_str(alt);
+                    text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    if skipping.is_none() {
+        text.push_str(&input[last..]);
+    }
+    blocks.push(&text, kind);
+    blocks.blocks
+}
+
+/// Strip inline Markdown and HTML: keep link and image text, drop URLs and emphasis markers
+fn clean_inline(text: &str, decode: bool) -> String {
+    lazy_static! {
+        static ref RULES: Vec<(Regex, &'static str)> = [
+            (r"!\[([^\]]*)\]\([^)]*\)", "$1"),
+            (r"\[\^[^\]]+\]", ""),
+            (r"\[([^\]]+)\]\([^)]*\)", "$1"),
+            (r"\[([^\]]+)\]\[[^\]]*\]", "$1"),
+            (r"<(?:https?|ftp|mailto):[^>\s]+>", ""),
+            (r"</?[A-Za-z][^>]*>", " "),
+            (r#"\b(?:https?://|www\.)[^\s<>()\[\]]*[^\s<>()\[\].,;:!?'"]"#, ""),
+            (r"`+([^`]+?)`+", "$1"),
+            (r"\*\*([^*]+)\*\*", "$1"),
+            (r"__([^_]+)__", "$1"),
+            (r"\*([^*\s][^*]*)\*", "$1"),
+            (r"\b_([^_]+)_\b", "$1"),
+            (r"~~([^~]+)~~", "$1"),
+            (r"\\([\\`*_{}\[\]()#+\-.!>~|])", "$1"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), replacement))
+        .collect();
+        // Left behind when a URL was the only thing in parentheses
+        static ref EMPTY_BRACKETS: Regex = Regex::new(r"\(\s*\)|\[\s*\]").unwrap();
+        static ref SPACE_BEFORE_PUNCTUATION: Regex = Regex::new(r"\s+([.,;:!?])").unwrap();
+    }
+
+    let mut text = text.to_string();
+    for (pattern, replacement) in RULES.iter() {
+        text = pattern.replace_all(&text, *replacement).into_owned();
+    }
+    let mut text = EMPTY_BRACKETS.replace_all(&text, "").into_owned();
+    if decode {
+        text = decode_entities(&text);
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    SPACE_BEFORE_PUNCTUATION.replace_all(&text, "$1").into_owned()
+}
+
+/// Decode the HTML entities that show up in ordinary prose
+fn decode_entities(text: &str) -> String {
+    lazy_static! {
+        static ref ENTITY: Regex = Regex::new(r"&(#[0-9]+|#[xX][0-9A-Fa-f]+|[A-Za-z]+);").unwrap();
+    }
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "lsquo" | "rsquo" => Some('\''),
+                "ldquo" | "rdquo" => Some('"'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
@@ -1,6 +1,16 @@
+pub mod builder;
 pub mod kitten;
+pub mod markup;
+#[cfg(feature = "bundled-espeak")]
+pub mod espeak_data;
+#[cfg(feature = "g2p")]
+pub mod g2p;
 pub mod phonemizer;
+pub mod request;
 pub mod tokenizer;
 pub mod text_cleaner;
+pub mod voices;
 
-pub use kitten::KittenTTS;
\ No newline at end of file
+pub use builder::{KittenTTSBuilder, LogLevel, PostProcessing};
+pub use kitten::{KittenTTS, DEFAULT_VOICE};
+pub use request::{SynthesisRequest, SynthesizedAudio};
\ No newline at end of file
@@ -1,7 +1,116 @@
 use anyhow::Result;
+#[cfg(all(feature = "espeak", not(feature = "bundled-espeak")))]
 use espeak_rs::text_to_phonemes;
 use whatlang::{detect, Lang};
 
+#[cfg(feature = "g2p")]
+use crate::tts::g2p::G2p;
+
+#[cfg(feature = "g2p")]
+lazy_static::lazy_static! {
+    static ref G2P: G2p = G2p::new();
+}
+
+/// Converts text into IPA phonemes for the model.
+///
+/// Implement this to plug in your own G2P service or dictionary and pass it to
+/// `KittenTTS::with_phonemizer`. Implementations return raw IPA with
+/// punctuation preserved; tokenization is applied afterwards.
+pub trait Phonemizer: Send + Sync {
+    fn phonemize(&self, text: &str, language: &str) -> Result<String>;
+}
+
+/// espeak-ng backed phonemizer (all languages espeak supports)
+#[cfg(feature = "espeak")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EspeakPhonemizer;
+
+#[cfg(feature = "espeak")]
+impl Phonemizer for EspeakPhonemizer {
+    fn phonemize(&self, text: &str, language: &str) -> Result<String> {
+        // The bundled build drives espeak-ng directly so it can pass the data directory
+        #[cfg(feature = "bundled-espeak")]
+        let phonemes = crate::tts::espeak_data::text_to_phonemes(text, language).ok();
+
+        // Use espeak for phonemization with preserve_punctuation=true and with_stress=true
+        // to match the Python implementation
+        #[cfg(not(feature = "bundled-espeak"))]
+        let phonemes = text_to_phonemes(text, language, None, true, true).ok().map(|phonemes| phonemes.join(""));
+
+        Ok(phonemes.unwrap_or_else(|| fallback_phonemes(text)))
+    }
+}
+
+/// Pure-Rust English phonemizer (CMUdict lexicon + letter-to-sound rules)
+#[cfg(feature = "g2p")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct G2pPhonemizer;
+
+#[cfg(feature = "g2p")]
+impl Phonemizer for G2pPhonemizer {
+    fn phonemize(&self, text: &str, _language: &str) -> Result<String> {
+        Ok(G2P.phonemize(text))
+    }
+}
+
+#[cfg(all(feature = "espeak", feature = "g2p"))]
+fn fallback_phonemes(text: &str) -> String {
+    // The G2P backend only knows English, but that still beats feeding raw letters to the model
+    G2P.phonemize(text)
+}
+
+#[cfg(all(feature = "espeak", not(feature = "g2p")))]
+fn fallback_phonemes(text: &str) -> String {
+    // Fallback: use basic tokenization on original text
+    basic_english_tokenize(text).join(" ")
+}
+
+/// Built-in phonemizer backends, selectable at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PhonemizerBackend {
+    /// espeak-ng (all languages), falling back to the G2P backend on failure
+    Espeak,
+    /// Pure-Rust English G2P (CMUdict lexicon + letter-to-sound rules)
+    G2p,
+}
+
+impl Default for PhonemizerBackend {
+    fn default() -> Self {
+        if cfg!(feature = "espeak") {
+            PhonemizerBackend::Espeak
+        } else {
+            PhonemizerBackend::G2p
+        }
+    }
+}
+
+impl PhonemizerBackend {
+    /// Instantiate the backend, failing if it was not compiled into this build
+    pub fn build(self) -> Result<Box<dyn Phonemizer>> {
+        match self {
+            #[cfg(feature = "espeak")]
+            PhonemizerBackend::Espeak => {
+                // Find espeak-ng-data now rather than on the first phonemize call
+                #[cfg(feature = "bundled-espeak")]
+                crate::tts::espeak_data::initialize()?;
+                Ok(Box::new(EspeakPhonemizer))
+            }
+            #[cfg(feature = "g2p")]
+            PhonemizerBackend::G2p => Ok(Box::new(G2pPhonemizer)),
+            #[allow(unreachable_patterns)]
+            other => anyhow::bail!(
+                "Phonemizer backend {:?} is not available: kittenx was built without the `{}` feature",
+                other,
+                match other {
+                    PhonemizerBackend::Espeak => "espeak",
+                    PhonemizerBackend::G2p => "g2p",
+                }
+            ),
+        }
+    }
+}
+
 pub fn detect_language(text: &str) -> Option<String> {
     if let Some(info) = detect(text) {
         let lang_code = match info.lang() {
@@ -24,21 +133,16 @@ pub fn detect_language(text: &str) -> Option<String> {
 }
 
 pub fn text_to_phonemes_simple(text: &str, language: &str) -> Result<String> {
-    // Use espeak for phonemization with preserve_punctuation=true and with_stress=true
-    // to match the Python implementation
-    match text_to_phonemes(text, language, None, true, true) {
-        Ok(phonemes) => {
-            let phonemes_str = phonemes.join("");
-            // Apply the same tokenization as Python's basic_english_tokenize
-            let tokens = basic_english_tokenize(&phonemes_str);
-            Ok(tokens.join(" "))
-        },
-        Err(_) => {
-            // Fallback: use basic tokenization on original text
-            let tokens = basic_english_tokenize(text);
-            Ok(tokens.join(" "))
-        }
-    }
+    let phonemizer = PhonemizerBackend::default().build()?;
+    phonemize_with(phonemizer.as_ref(), text, language)
+}
+
+/// Phonemize with the given backend and tokenize the result the way the model expects
+pub fn phonemize_with(phonemizer: &dyn Phonemizer, text: &str, language: &str) -> Result<String> {
+    let phonemes_str = phonemizer.phonemize(text, language)?;
+    // Apply the same tokenization as Python's basic_english_tokenize
+    let tokens = basic_english_tokenize(&phonemes_str);
+    Ok(tokens.join(" "))
 }
 
 pub fn basic_english_tokenize(text: &str) -> Vec<String> {
@@ -52,4 +156,4 @@ pub fn basic_english_tokenize(text: &str) -> Vec<String> {
 
 pub fn basic_tokenize(text: &str) -> Vec<String> {
     basic_english_tokenize(text)
-}
\ No newline at end of file
+}
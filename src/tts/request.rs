@@ -0,0 +1,103 @@
+use crate::tts::markup::{CodeBlocks, InputFormat};
+use crate::utils::save_wav;
+use anyhow::Result;
+use std::path::Path;
+
+/// Per-call synthesis options.
+///
+/// Options left unset fall back to the defaults configured with `KittenTTS::builder()`.
+/// Build it with `SynthesisRequest::new` and the setters; new options always come
+/// with a default, so existing call sites keep compiling.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SynthesisRequest {
+    pub text: String,
+    pub voice: Option<String>,
+    /// Speech speed (1.0 = normal)
+    pub speed: Option<f32>,
+    /// espeak language code (`en-us`, `de`, ...), or `auto` to detect it from the text
+    pub language: Option<String>,
+    /// Pitch shift in semitones, from -12 to 12; the duration stays the same
+    pub pitch: f32,
+    /// Gain applied to the samples (1.0 = unchanged)
+    pub volume: f32,
+    /// Resample to this rate instead of returning the model's native rate
+    pub output_sample_rate: Option<u32>,
+    /// Markup to strip from `text` before speaking it (see `tts::markup`)
+    pub input_format: InputFormat,
+    /// Whether code blocks in Markdown or HTML input are announced or skipped
+    pub code_blocks: CodeBlocks,
+}
+
+impl SynthesisRequest {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            voice: None,
+            speed: None,
+            language: None,
+            pitch: 0.0,
+            volume: 1.0,
+            output_sample_rate: None,
+            input_format: InputFormat::default(),
+            code_blocks: CodeBlocks::default(),
+        }
+    }
+
+    pub fn voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn pitch(mut self, semitones: f32) -> Self {
+        self.pitch = semitones;
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn output_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.output_sample_rate = Some(sample_rate);
+        self
+    }
+
+    pub fn input_format(mut self, input_format: InputFormat) -> Self {
+        self.input_format = input_format;
+        self
+    }
+
+    pub fn code_blocks(mut self, code_blocks: CodeBlocks) -> Self {
+        self.code_blocks = code_blocks;
+        self
+    }
+}
+
+/// Samples produced by `KittenTTS::synthesize`, with the rate they are at
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthesizedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl SynthesizedAudio {
+    pub fn duration_secs(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    pub fn save_wav(&self, path: &Path) -> Result<()> {
+        save_wav(&self.samples, self.sample_rate, path)
+    }
+}
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref SYMBOL_TO_ID: HashMap<char, i64> = {
+    /// Model vocabulary; a symbol's token id is its index
+    static ref SYMBOLS: Vec<char> = {
         let pad = "$";
         let punctuation = ";:,.!?¡¿—…\"«»\"\" ";
         let letters = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
@@ -13,9 +14,12 @@ lazy_static! {
         symbols.extend(punctuation.chars());
         symbols.extend(letters.chars());
         symbols.extend(letters_ipa.chars());
+        symbols
+    };
 
+    static ref SYMBOL_TO_ID: HashMap<char, i64> = {
         let mut map = HashMap::new();
-        for (i, symbol) in symbols.iter().enumerate() {
+        for (i, symbol) in SYMBOLS.iter().enumerate() {
             map.insert(*symbol, i as i64);
         }
         map
@@ -38,10 +42,44 @@ impl TextCleaner {
         }
         tokens
     }
+
+    /// Characters of `text` that `clean` drops because the model has no token for them,
+    /// with their char offsets
+    pub fn dropped_chars(&self, text: &str) -> Vec<(usize, char)> {
+        text.chars().enumerate().filter(|(_, ch)| !SYMBOL_TO_ID.contains_key(ch)).collect()
+    }
+
+    /// Symbol a token id stands for
+    pub fn symbol(&self, id: i64) -> Option<char> {
+        usize::try_from(id).ok().and_then(|i| SYMBOLS.get(i)).copied()
+    }
+
+    /// Number of token ids the cleaner can produce
+    pub fn vocab_size(&self) -> usize {
+        SYMBOL_TO_ID.values().max().map_or(0, |&id| id as usize + 1)
+    }
 }
 
 impl Default for TextCleaner {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Split text after sentence-ending punctuation followed by whitespace (or the end),
+/// keeping the punctuation. Used to stream long texts one sentence at a time.
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = !matches!(chars.peek(), Some(&(_, next)) if !next.is_whitespace());
+        if matches!(c, '.' | '!' | '?') && at_boundary {
+            let end = i + c.len_utf8();
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+    }
+    sentences.push(&text[start..]);
+    sentences.into_iter().map(str::trim).filter(|s| !s.is_empty()).collect()
+}
@@ -0,0 +1,48 @@
+// Voice style vectors, as shipped in a model's voices NPZ file
+
+use anyhow::Result;
+use ndarray::{Array2, Ix1, Ix2, OwnedRepr};
+use ndarray_npy::NpzReader;
+use std::io::{Read, Seek};
+
+/// Voices read from an NPZ archive, in archive order
+pub struct VoiceArchive {
+    /// Each voice as a `[rows, style_dim]` matrix
+    pub voices: Vec<(String, Array2<f32>)>,
+    /// Entries that aren't 1-D or 2-D f32 arrays, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Read every voice in an NPZ archive. Older models ship one style vector per voice;
+/// newer ones ship one per input length.
+pub fn read_voices<R: Read + Seek>(reader: R) -> Result<VoiceArchive> {
+    let mut npz = NpzReader::new(reader)?;
+    let mut archive = VoiceArchive { voices: Vec::new(), skipped: Vec::new() };
+
+    for voice_name in npz.names()? {
+        // First try loading as 2D array (which is the actual format in the NPZ file)
+        match npz.by_name::<OwnedRepr<f32>, Ix2>(&voice_name) {
+            Ok(voice_array) => archive.voices.push((voice_name, voice_array)),
+            // Fallback: try loading as 1D array
+            Err(_) => match npz.by_name::<OwnedRepr<f32>, Ix1>(&voice_name) {
+                Ok(voice_array) => {
+                    let len = voice_array.len();
+                    archive.voices.push((voice_name, voice_array.into_shape_with_order((1, len))?));
+                }
+                Err(e) => archive.skipped.push((voice_name, e.to_string())),
+            },
+        }
+    }
+
+    if archive.voices.is_empty() {
+        anyhow::bail!("No voices could be loaded from the NPZ file");
+    }
+    Ok(archive)
+}
+
+/// Style vector for `text`. Voices with several rows are indexed by text length,
+/// matching the reference implementation.
+pub fn style_for_text(styles: &Array2<f32>, text: &str) -> Vec<f32> {
+    let row = text.chars().count().min(styles.nrows().saturating_sub(1));
+    styles.row(row).to_vec()
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use hound::{WavSpec, WavWriter};
+use std::io::Cursor;
 use std::path::Path;
 
 pub fn save_wav(audio: &[f32], sample_rate: u32, path: &Path) -> Result<()> {
@@ -43,6 +44,60 @@ pub fn save_wav_16bit(audio: &[f32], sample_rate: u32, path: &Path) -> Result<()
     Ok(())
 }
 
+/// Encoding used when saving audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AudioFormat {
+    /// 32-bit float WAV
+    #[default]
+    Wav,
+    /// 16-bit PCM WAV
+    Wav16,
+    /// Headerless little-endian f32 samples
+    Raw,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav | AudioFormat::Wav16 => "wav",
+            AudioFormat::Raw => "raw",
+        }
+    }
+
+    /// Encode `audio` in memory, e.g. to write it to stdout
+    pub fn encode(&self, audio: &[f32], sample_rate: u32) -> Result<Vec<u8>> {
+        if *self == AudioFormat::Raw {
+            return Ok(audio.iter().flat_map(|s| s.clamp(-1.0, 1.0).to_le_bytes()).collect());
+        }
+        let mut bytes = Vec::new();
+        if *self == AudioFormat::Wav16 {
+            let spec = WavSpec { channels: 1, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+            let mut writer = WavWriter::new(Cursor::new(&mut bytes), spec)?;
+            for &sample in audio {
+                writer.write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)?;
+            }
+            writer.finalize()?;
+        } else {
+            let spec = WavSpec { channels: 1, sample_rate, bits_per_sample: 32, sample_format: hound::SampleFormat::Float };
+            let mut writer = WavWriter::new(Cursor::new(&mut bytes), spec)?;
+            for &sample in audio {
+                writer.write_sample(sample.clamp(-1.0, 1.0))?;
+            }
+            writer.finalize()?;
+        }
+        Ok(bytes)
+    }
+
+    pub fn save(&self, audio: &[f32], sample_rate: u32, path: &Path) -> Result<()> {
+        match self {
+            AudioFormat::Wav => save_wav(audio, sample_rate, path),
+            AudioFormat::Wav16 => save_wav_16bit(audio, sample_rate, path),
+            AudioFormat::Raw => Ok(std::fs::write(path, self.encode(audio, sample_rate)?)?),
+        }
+    }
+}
+
 /// Compute frame-wise RMS energy
 fn rms_frames(audio: &[f32], frame_len: usize, hop_len: usize) -> Vec<f32> {
     if audio.is_empty() || frame_len == 0 { return vec![]; }
@@ -61,6 +116,29 @@ fn rms_frames(audio: &[f32], frame_len: usize, hop_len: usize) -> Vec<f32> {
     rms
 }
 
+/// Parameters of `trim_silence`. The defaults avoid cutting off final words while
+/// removing obvious head and tail silence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrimOptions {
+    pub top_db: f32,
+    pub frame_ms: f32,
+    pub hop_ms: f32,
+    pub min_silence_ms: f32,
+    pub end_padding_ms: f32,
+}
+
+impl Default for TrimOptions {
+    fn default() -> Self {
+        Self { top_db: 40.0, frame_ms: 20.0, hop_ms: 10.0, min_silence_ms: 150.0, end_padding_ms: 80.0 }
+    }
+}
+
+impl TrimOptions {
+    pub fn apply(&self, audio: &[f32], sample_rate: u32) -> Vec<f32> {
+        trim_silence(audio, sample_rate, self.top_db, self.frame_ms, self.hop_ms, self.min_silence_ms, self.end_padding_ms)
+    }
+}
+
 /// Trim leading/trailing silence using an RMS threshold (librosa-like)
 /// - top_db: threshold in dB relative to max RMS (e.g., 40.0)
 /// - frame_ms/hop_ms: analysis window and hop in milliseconds
@@ -164,3 +242,92 @@ pub fn apply_fade_in_out(audio: &mut [f32], sample_rate: u32, fade_in_ms: f32, f
         audio[idx] *= 1.0 - gain;
     }
 }
+
+/// How closely a waveform matches a reference rendering of the same input
+#[derive(Debug, Clone, Copy)]
+pub struct WaveformComparison {
+    /// Reference power over the power of the sample-wise difference, in dB
+    pub snr_db: f32,
+    /// Correlation of the 20 ms RMS envelopes (1.0 = same loudness contour)
+    pub envelope_correlation: f32,
+    /// Candidate length divided by reference length
+    pub length_ratio: f32,
+}
+
+/// Compare `candidate` against `reference` over their common length.
+/// Sample-wise SNR punishes tiny phase shifts, so the envelope correlation is the
+/// better indicator of audible differences.
+pub fn compare_waveforms(reference: &[f32], candidate: &[f32], sample_rate: u32) -> WaveformComparison {
+    let len = reference.len().min(candidate.len());
+    let (mut signal, mut noise) = (0.0f64, 0.0f64);
+    for (&r, &c) in reference[..len].iter().zip(&candidate[..len]) {
+        signal += f64::from(r) * f64::from(r);
+        noise += f64::from(r - c) * f64::from(r - c);
+    }
+    let snr_db = if noise == 0.0 { f32::INFINITY } else { (10.0 * (signal / noise).log10()) as f32 };
+
+    let frame_len = ((sample_rate as usize) / 50).max(1);
+    let a = rms_frames(&reference[..len], frame_len, frame_len);
+    let b = rms_frames(&candidate[..len], frame_len, frame_len);
+    let envelope_correlation = correlation(&a, &b);
+
+    let length_ratio = if reference.is_empty() { 0.0 } else { candidate.len() as f32 / reference.len() as f32 };
+    WaveformComparison { snr_db, envelope_correlation, length_ratio }
+}
+
+/// Pearson correlation of two equally long series
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    if n == 0 { return 0.0; }
+    let mean_a = a[..n].iter().map(|&v| f64::from(v)).sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().map(|&v| f64::from(v)).sum::<f64>() / n as f64;
+    let (mut cov, mut var_a, mut var_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (&x, &y) in a[..n].iter().zip(&b[..n]) {
+        let (dx, dy) = (f64::from(x) - mean_a, f64::from(y) - mean_b);
+        cov += dx * dy;
+        var_a += dx * dx;
+        var_b += dy * dy;
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return if var_a == var_b { 1.0 } else { 0.0 };
+    }
+    (cov / (var_a.sqrt() * var_b.sqrt())) as f32
+}
+
+/// Resample from `from_rate` to `to_rate` Hz
+pub fn resample(audio: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    resample_by(audio, f64::from(to_rate) / f64::from(from_rate))
+}
+
+/// Resample by `ratio` (output length / input length) with a Hann-windowed sinc filter.
+/// When shrinking, the cutoff drops with the ratio so nothing aliases.
+pub fn resample_by(audio: &[f32], ratio: f64) -> Vec<f32> {
+    use std::f64::consts::PI;
+    const ZERO_CROSSINGS: f64 = 16.0;
+
+    if audio.is_empty() || ratio.is_nan() || ratio <= 0.0 || (ratio - 1.0).abs() < 1e-9 {
+        return audio.to_vec();
+    }
+    let cutoff = ratio.min(1.0);
+    let half_width = ZERO_CROSSINGS / cutoff;
+    let out_len = (audio.len() as f64 * ratio).round() as usize;
+    let last = audio.len() as f64 - 1.0;
+
+    (0..out_len)
+        .map(|i| {
+            let center = i as f64 / ratio;
+            let start = (center - half_width).ceil().max(0.0);
+            let end = (center + half_width).floor().min(last);
+            if start > end { return 0.0; }
+            let mut acc = 0.0f64;
+            for (j, &sample) in audio.iter().enumerate().take(end as usize + 1).skip(start as usize) {
+                let x = j as f64 - center;
+                let t = x * cutoff;
+                let sinc = if t.abs() < 1e-9 { 1.0 } else { (PI * t).sin() / (PI * t) };
+                let window = 0.5 + 0.5 * (PI * x / half_width).cos();
+                acc += f64::from(sample) * sinc * window * cutoff;
+            }
+            acc as f32
+        })
+        .collect()
+}
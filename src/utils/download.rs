@@ -1,21 +1,355 @@
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result};
+use reqwest::{header, StatusCode};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Bytes received so far and the full size if the server reported it
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+pub type ProgressCallback = Arc<dyn Fn(DownloadProgress) + Send + Sync>;
+
+/// Hash a download is checked against
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Checksum {
+    Sha256(String),
+    /// Git blob id (SHA-1 of `blob <size>\0<content>`), which the Hub sends as the ETag of
+    /// files that are not stored in LFS, such as config.json
+    GitBlob(String),
+}
+
+impl std::fmt::Display for Checksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Checksum::Sha256(hash) => write!(f, "SHA-256 {}", hash),
+            Checksum::GitBlob(hash) => write!(f, "git blob id {}", hash),
+        }
+    }
+}
+
+/// HTTP downloader that never leaves a partial or unverified file at the destination.
+///
+/// Data is streamed into `<file>.part`, which is resumed with a Range request after an
+/// interruption, checked against the expected SHA-256 and only then renamed into place.
+/// Any URL works, so it can be pointed at a local HTTP server.
+#[derive(Clone)]
+pub struct Downloader {
+    client: reqwest::Client,
+    max_retries: u32,
+    initial_backoff: Duration,
+    progress: Option<ProgressCallback>,
+    require_checksum: bool,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Outcome of one attempt that did not succeed
+enum AttemptError {
+    /// Worth trying again (network error, 5xx, 408, 429, truncated body, mismatch with the
+    /// hash the server advertised), after the delay the server asked for in `Retry-After`, if any
+    Retry(anyhow::Error, Option<Duration>),
+    /// Trying again will not help (404, 403, local I/O failure, mismatch with the SHA-256
+    /// passed to `download`)
+    Fatal(anyhow::Error),
+}
+
+impl<E: Into<anyhow::Error>> From<E> for AttemptError {
+    fn from(e: E) -> Self {
+        AttemptError::Retry(e.into(), None)
+    }
+}
+
+/// Longest `Retry-After` delay honoured, so a misbehaving server can't stall a download
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+impl Downloader {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            progress: None,
+            require_checksum: false,
+        }
+    }
+
+    /// Number of retries after the first attempt
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry; doubled for every further retry
+    pub fn with_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Fail instead of warning when no hash is known for the file, so nothing unverified is
+    /// ever moved into place
+    pub fn with_required_checksum(mut self, required: bool) -> Self {
+        self.require_checksum = required;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: impl Fn(DownloadProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Download `url` to `path`.
+    ///
+    /// The file is verified against `expected_sha256` when given, otherwise against the
+    /// SHA-256 the server advertises in `X-Linked-Etag`/`ETag` (as HuggingFace does for
+    /// LFS files) or, failing that, the git blob id in `ETag` (the Hub's other files).
+    /// When none is available this is an error with `with_required_checksum`, and a
+    /// warning otherwise. A file that doesn't match `expected_sha256` is rejected without
+    /// retrying: the server is handing out different content than the caller pinned.
+    pub async fn download(&self, url: &str, path: &Path, expected_sha256: Option<&str>) -> Result<()> {
+        eprintln!("Downloading {} to {}", url, path.display());
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let part = partial_path(path);
+
+        // The Hub only advertises the hash on the redirect, which the GET below follows
+        let pinned = expected_sha256.is_some();
+        let expected = match expected_sha256 {
+            Some(sha256) => Some(Checksum::Sha256(sha256.to_ascii_lowercase())),
+            None => match remote_headers(url).await {
+                Ok(headers) => advertised_checksum(&headers),
+                Err(e) if self.require_checksum => {
+                    return Err(e.context(format!("Could not look up the hash of {}", url)));
+                }
+                Err(e) => {
+                    eprintln!("Warning: could not look up the SHA-256 of {} ({:#})", url, e);
+                    None
+                }
+            },
+        };
+
+        let mut attempt = 0;
+        loop {
+            match self.attempt(url, &part, expected.as_ref(), pinned).await {
+                Ok((size, verified)) => {
+                    if verified.is_none() && self.require_checksum {
+                        let _ = fs::remove_file(&part).await;
+                        anyhow::bail!("No hash is known for {}, refusing to install it unverified", url);
+                    }
+                    fs::rename(&part, path).await
+                        .with_context(|| format!("Failed to move download into place at {}", path.display()))?;
+                    if let Some(checksum) = verified {
+                        eprintln!("Downloaded {} ({} bytes, {} verified)", path.display(), size, checksum);
+                    } else {
+                        eprintln!(
+                            "Warning: downloaded {} ({} bytes) without an integrity check, no SHA-256 is known for it",
+                            path.display(),
+                            size
+                        );
+                    }
+                    return Ok(());
+                }
+                Err(AttemptError::Fatal(e)) => return Err(e),
+                Err(AttemptError::Retry(e, retry_after)) if attempt < self.max_retries => {
+                    let backoff = self.initial_backoff * 2u32.saturating_pow(attempt);
+                    let delay = retry_after.map_or(backoff, |after| after.min(MAX_RETRY_AFTER));
+                    attempt += 1;
+                    eprintln!(
+                        "Download of {} failed ({:#}), retrying in {:?} ({}/{})",
+                        url, e, delay, attempt, self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(AttemptError::Retry(e, _)) => {
+                    return Err(e.context(format!("Failed to download {} after {} attempts", url, attempt + 1)));
+                }
+            }
+        }
+    }
+
+    /// One request, resuming from whatever is already in `part`. Returns the final size and
+    /// the hash it was checked against, if any. A mismatch is fatal when `expected` is `pinned`.
+    async fn attempt(
+        &self,
+        url: &str,
+        part: &Path,
+        expected: Option<&Checksum>,
+        pinned: bool,
+    ) -> Result<(u64, Option<Checksum>), AttemptError> {
+        let offset = match fs::metadata(part).await {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        };
+
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(header::RANGE, format!("bytes={}-", offset));
+        }
+        let mut response = request.send().await?;
+        let status = response.status();
+
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial file is stale or already complete but unverifiable; start over
+            let _ = fs::remove_file(part).await;
+            return Err(AttemptError::Retry(anyhow::anyhow!("server rejected resume at byte {}", offset), None));
+        }
+        let transient = status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS;
+        if status.is_client_error() && !transient {
+            return Err(AttemptError::Fatal(anyhow::anyhow!("HTTP {} for {}", status, url)));
+        }
+        if !status.is_success() {
+            let retry_after = retry_after(response.headers());
+            return Err(AttemptError::Retry(anyhow::anyhow!("HTTP {} for {}", status, url), retry_after));
+        }
+
+        let expected = expected.cloned().or_else(|| advertised_checksum(response.headers()));
+
+        // 206 continues the partial file; 200 means the server ignored the range
+        let resumed = status == StatusCode::PARTIAL_CONTENT && offset > 0;
+        let mut downloaded = if resumed { offset } else { 0 };
+        let total = response.content_length().map(|len| len + downloaded);
+
+        let mut file = if resumed {
+            fs::OpenOptions::new().append(true).open(part).await
+        } else {
+            fs::File::create(part).await
+        }
+        .map_err(|e| AttemptError::Fatal(anyhow::anyhow!("Failed to open {}: {}", part.display(), e)))?;
+
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await
+                .map_err(|e| AttemptError::Fatal(anyhow::anyhow!("Failed to write {}: {}", part.display(), e)))?;
+            downloaded += chunk.len() as u64;
+            if let Some(progress) = &self.progress {
+                progress(DownloadProgress { downloaded, total });
+            }
+        }
+        file.flush().await?;
+        file.sync_all().await?;
+        drop(file);
+
+        if let Some(total) = total {
+            if downloaded < total {
+                return Err(AttemptError::Retry(
+                    anyhow::anyhow!("connection closed after {} of {} bytes", downloaded, total),
+                    None,
+                ));
+            }
+        }
+
+        if let Some(expected) = &expected {
+            let (kind, wanted, actual) = match expected {
+                Checksum::Sha256(hash) => ("SHA-256", hash, sha256_file(part).await?),
+                Checksum::GitBlob(hash) => ("Git blob id", hash, git_blob_id_file(part).await?),
+            };
+            if &actual != wanted {
+                let _ = fs::remove_file(part).await;
+                let error = anyhow::anyhow!("{} mismatch: expected {}, got {}", kind, wanted, actual);
+                return Err(if pinned {
+                    AttemptError::Fatal(error)
+                } else {
+                    AttemptError::Retry(error, None)
+                });
+            }
+        }
+
+        Ok((downloaded, expected))
+    }
+}
 
 pub async fn download_file(url: &str, path: &Path) -> Result<()> {
-    println!("Downloading {} to {}", url, path.display());
-    
-    let response = reqwest::get(url).await?;
-    let bytes = response.bytes().await?;
-    
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await?;
-    }
-    
-    let mut file = fs::File::create(path).await?;
-    file.write_all(&bytes).await?;
-    
-    println!("Downloaded {} ({} bytes)", path.display(), bytes.len());
-    Ok(())
-}
\ No newline at end of file
+    Downloader::new().download(url, path, None).await
+}
+
+/// SHA-256 the server advertises for `url`, without downloading it or following redirects
+pub async fn remote_sha256(url: &str) -> Result<Option<String>> {
+    Ok(advertised_sha256(&remote_headers(url).await?))
+}
+
+/// Response headers of a HEAD request for `url`, without following redirects (the Hub
+/// reports a file's commit and hash on the redirect)
+pub async fn remote_headers(url: &str) -> Result<header::HeaderMap> {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let response = client.head(url).send().await?;
+    let status = response.status();
+    anyhow::ensure!(
+        status.is_success() || status.is_redirection(),
+        "HTTP {} for {}",
+        status,
+        url
+    );
+    Ok(response.headers().clone())
+}
+
+/// Lowercase hex SHA-256 of a file
+pub async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Git blob id of a file: the SHA-1 of `blob <size>\0` followed by its content
+async fn git_blob_id_file(path: &Path) -> Result<String> {
+    let content = fs::read(path).await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", content.len()).as_bytes());
+    hasher.update(&content);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// `<file>.part` next to the destination
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Delay from a `Retry-After` header in seconds (the HTTP-date form falls back to backoff)
+fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// SHA-256 from `X-Linked-Etag` (HuggingFace LFS) or `ETag`, if it looks like one
+pub fn advertised_sha256(headers: &header::HeaderMap) -> Option<String> {
+    ["x-linked-etag", "etag"]
+        .iter()
+        .filter_map(|name| headers.get(*name)?.to_str().ok())
+        .map(|value| value.trim_start_matches("W/").trim_matches('"').to_ascii_lowercase())
+        .find(|value| value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Advertised SHA-256, or else the git blob id the Hub sends as the `ETag` of non-LFS files
+fn advertised_checksum(headers: &header::HeaderMap) -> Option<Checksum> {
+    advertised_sha256(headers).map(Checksum::Sha256).or_else(|| {
+        let etag = headers.get(header::ETAG)?.to_str().ok()?;
+        let etag = etag.trim_start_matches("W/").trim_matches('"').to_ascii_lowercase();
+        let is_blob_id = etag.len() == 40 && etag.chars().all(|c| c.is_ascii_hexdigit());
+        is_blob_id.then_some(Checksum::GitBlob(etag))
+    })
+}
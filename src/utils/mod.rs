@@ -1,5 +1,7 @@
+#[cfg(feature = "download")]
 pub mod download;
 pub mod audio;
 
+#[cfg(feature = "download")]
 pub use download::*;
-pub use audio::*;
\ No newline at end of file
+pub use audio::*;
@@ -0,0 +1,44 @@
+// Browser bindings (the `wasm` feature), built with wasm-pack for wasm32-unknown-unknown
+// The model, voices and config are passed in as bytes, so synthesis needs neither a file
+// system nor a network; inference runs on tract and phonemes come from the G2P backend.
+
+use crate::tts::KittenTTS;
+use wasm_bindgen::prelude::*;
+
+fn js_error(e: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", e))
+}
+
+/// A loaded model, exported to JavaScript as `KittenTTS`
+#[wasm_bindgen(js_name = KittenTTS)]
+pub struct WasmKittenTTS {
+    tts: KittenTTS,
+}
+
+#[wasm_bindgen(js_class = KittenTTS)]
+impl WasmKittenTTS {
+    /// Load a model from the bytes of its `.onnx` file and voices NPZ, plus the text of
+    /// its `config.json` if there is one
+    #[wasm_bindgen(constructor)]
+    pub fn new(model: &[u8], voices: &[u8], config: Option<String>) -> Result<WasmKittenTTS, JsError> {
+        let tts = KittenTTS::from_bytes(model, voices, config.as_deref()).map_err(js_error)?;
+        Ok(Self { tts })
+    }
+
+    /// Names of the voices the model provides
+    #[wasm_bindgen(getter)]
+    pub fn voices(&self) -> Vec<String> {
+        self.tts.available_voices().to_vec()
+    }
+
+    /// Sample rate of the generated audio in Hz
+    #[wasm_bindgen(getter, js_name = sampleRate)]
+    pub fn sample_rate(&self) -> u32 {
+        self.tts.sample_rate()
+    }
+
+    /// Synthesize English `text`, returning mono samples as a `Float32Array`
+    pub fn generate(&self, text: &str, voice: &str, speed: f32) -> Result<Vec<f32>, JsError> {
+        self.tts.generate(text, voice, speed).map_err(js_error)
+    }
+}
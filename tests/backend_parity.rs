@@ -0,0 +1,69 @@
+// Checks that the tract backend matches ONNX Runtime.
+// A small synthetic model with the KittenTTS inputs always runs. The bundled model check is
+// ignored by default; run it with `cargo test --test backend_parity -- --ignored` once its
+// .onnx file is in models/ (or in KITTENX_TEST_MODEL_DIR).
+
+mod common;
+
+use common::synthetic_model;
+use kittenx::onnx::{Backend, InferenceBackend};
+use kittenx::utils::compare_waveforms;
+use kittenx::{AccelerationProvider, KittenTTS, SessionOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn load(backend: Backend, model_path: &Path) -> Arc<dyn InferenceBackend> {
+    backend
+        .load(model_path, AccelerationProvider::Cpu, &SessionOptions::default())
+        .unwrap_or_else(|e| panic!("{} failed to load {}: {:#}", backend, model_path.display(), e))
+}
+
+#[test]
+fn synthetic_model_matches() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("backend_parity.onnx");
+    std::fs::write(&path, synthetic_model()).expect("write synthetic model");
+    let ort = load(Backend::OnnxRuntime, &path);
+    let tract = load(Backend::Tract, &path);
+
+    let cases: [(Vec<i64>, [f32; 4], f32); 3] = [
+        (vec![0, 12, 47, 3, 0], [0.1, -0.2, 0.3, 0.05], 1.0),
+        (vec![0, 1, 0], [0.5, 0.5, -0.25, 0.0], 0.7),
+        ((0..64).collect(), [-0.01, 0.02, -0.03, 0.04], 1.5),
+    ];
+    for (tokens, style, speed) in cases {
+        let expected = ort.infer(vec![tokens.clone()], style.to_vec(), speed).expect("onnxruntime inference");
+        let actual = tract.infer(vec![tokens.clone()], style.to_vec(), speed).expect("tract inference");
+        assert_eq!(expected.shape(), actual.shape(), "output shapes differ for {:?}", tokens);
+        let max_diff = expected.iter().zip(actual.iter()).map(|(a, b)| (a - b).abs()).fold(0.0f32, f32::max);
+        assert!(max_diff < 1e-4, "outputs differ by {} for {:?}", max_diff, tokens);
+    }
+}
+
+#[test]
+#[ignore = "needs the bundled model in models/ or KITTENX_TEST_MODEL_DIR"]
+fn bundled_model_matches() {
+    let files = common::bundled_model_files()
+        .unwrap_or_else(|| panic!("no model in {}", common::bundled_model_dir().display()));
+
+    let tts = |backend: Backend| {
+        let options = SessionOptions { backend: Some(backend), ..SessionOptions::default() };
+        KittenTTS::from_files(&files, AccelerationProvider::Cpu, options).expect("load bundled model")
+    };
+    let (ort, tract) = (tts(Backend::OnnxRuntime), tts(Backend::Tract));
+    let voice = ort.default_voice().to_string();
+
+    for text in ["Hello world.", "The quick brown fox jumps over the lazy dog, twice!"] {
+        let expected = ort.generate_outputs(text, &voice, 1.0).expect("onnxruntime synthesis");
+        let actual = tract.generate_outputs(text, &voice, 1.0).expect("tract synthesis");
+        let expected: Vec<f32> = expected.waveform.iter().copied().collect();
+        let actual: Vec<f32> = actual.waveform.iter().copied().collect();
+        let comparison = compare_waveforms(&expected, &actual, ort.sample_rate());
+        println!(
+            "SNR {:.1} dB, envelope correlation {:.4}, length {:.3}x  \"{}\"",
+            comparison.snr_db, comparison.envelope_correlation, comparison.length_ratio, text
+        );
+        assert!((comparison.length_ratio - 1.0).abs() < 1e-3, "lengths differ for \"{}\"", text);
+        assert!(comparison.snr_db > 40.0, "SNR {} dB too low for \"{}\"", comparison.snr_db, text);
+        assert!(comparison.envelope_correlation > 0.999, "envelopes differ for \"{}\"", text);
+    }
+}
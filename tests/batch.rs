@@ -0,0 +1,40 @@
+// Checks that batched synthesis gives each request the audio it gets on its own, on the
+// synthetic model from tests/common (whose batch items are independent).
+
+#![cfg(all(feature = "g2p", any(feature = "onnxruntime", feature = "tract")))]
+
+mod common;
+
+use kittenx::tts::phonemizer::PhonemizerBackend;
+use kittenx::{KittenTTS, ModelFiles, PostProcessing};
+use std::path::PathBuf;
+
+#[test]
+fn batched_output_matches_per_item_synthesis() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("batch_model");
+    common::write_model_dir(&dir);
+    let tts = KittenTTS::builder()
+        .model_files(ModelFiles::new(dir.join("model.onnx"), dir.join("voices.npz")).with_config(dir.join("config.json")))
+        .phonemizer(PhonemizerBackend::G2p.build().unwrap())
+        .post_processing(PostProcessing { trim: None, normalize_peak: None })
+        .build()
+        .expect("load synthetic model");
+
+    // Mixed lengths, plus the same sentence at three speeds so that one run holds several items
+    let requests = [
+        ("Hello world.", common::VOICE, 1.0),
+        ("The quick brown fox jumps over the lazy dog, twice!", common::VOICE, 1.0),
+        ("Hello world.", common::VOICE, 1.3),
+        ("How are you?", common::VOICE, 0.8),
+        ("Hello world.", common::VOICE, 0.5),
+    ];
+    let batched = tts.generate_batch(&requests).expect("batched synthesis");
+    assert_eq!(batched.len(), requests.len());
+
+    for ((text, voice, speed), candidate) in requests.iter().zip(&batched) {
+        let reference = tts.generate(text, voice, *speed).expect("synthesis");
+        assert_eq!(reference.len(), candidate.len(), "lengths differ for \"{}\" at {}x", text, speed);
+        let max_diff = reference.iter().zip(candidate).map(|(a, b)| (a - b).abs()).fold(0.0f32, f32::max);
+        assert!(max_diff < 1e-5, "outputs differ by {} for \"{}\" at {}x", max_diff, text, speed);
+    }
+}
@@ -0,0 +1,34 @@
+// Runs `kittenx bench` once over the corpus on the synthetic model from tests/common.
+
+#![cfg(all(feature = "g2p", any(feature = "onnxruntime", feature = "tract")))]
+
+mod common;
+
+use kittenx::bench::{run_benchmark, BenchOptions, CORPUS};
+use kittenx::tts::phonemizer::PhonemizerBackend;
+use kittenx::{KittenTTS, ModelFiles, SessionOptions};
+use std::path::PathBuf;
+
+#[test]
+fn benchmark_runs_the_corpus_once() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bench_model");
+    common::write_model_dir(&dir);
+    let tts = KittenTTS::builder()
+        .model_files(ModelFiles::new(dir.join("model.onnx"), dir.join("voices.npz")).with_config(dir.join("config.json")))
+        .phonemizer(PhonemizerBackend::G2p.build().unwrap())
+        .build()
+        .expect("load synthetic model");
+    let options = BenchOptions { voice: common::VOICE.to_string(), speed: 1.0, warmup: 0, iterations: 1 };
+
+    let report = run_benchmark(&tts, &SessionOptions::default(), &options).expect("benchmark");
+
+    assert_eq!(report.iterations, 1);
+    assert_eq!(report.overall.runs, CORPUS.len());
+    let categories: Vec<&str> = report.categories.iter().map(|c| c.category.as_str()).collect();
+    assert_eq!(categories, ["short", "medium", "long"]);
+    assert_eq!(report.categories.iter().map(|c| c.runs).sum::<usize>(), CORPUS.len());
+    assert!(report.overall.mean_audio_seconds > 0.0);
+    assert!(report.overall.real_time_factor > 0.0);
+    assert!(report.overall.latency.min_ms <= report.overall.latency.max_ms);
+    assert!(report.to_string().contains("1st sent p50"));
+}
@@ -0,0 +1,88 @@
+/* Smoke test for the C API, built and run by tests/c_api.rs.
+ * Usage: smoke [model_dir]. Without a model directory only the error paths are exercised. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "kittenx.h"
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static int count_chunks(const float *samples, size_t len, void *user_data) {
+    (void)samples;
+    if (len > 0) {
+        ++*(int *)user_data;
+    }
+    return 0;
+}
+
+static int stop_after_first(const float *samples, size_t len, void *user_data) {
+    (void)samples;
+    (void)len;
+    (void)user_data;
+    return 1;
+}
+
+int main(int argc, char **argv) {
+    const char *version = kittenx_version();
+    CHECK(version != NULL && strlen(version) > 0);
+
+    /* Failures return null and leave a message */
+    CHECK(kittenx_engine_new(NULL, NULL) == NULL);
+    CHECK(kittenx_last_error() != NULL && strstr(kittenx_last_error(), "model_dir") != NULL);
+    CHECK(kittenx_engine_new(".", "not-a-provider") == NULL);
+    CHECK(strstr(kittenx_last_error(), "not-a-provider") != NULL);
+
+    /* Null engines are rejected rather than dereferenced */
+    size_t len = 0;
+    CHECK(kittenx_synthesize(NULL, "Hello", NULL, 1.0f, NULL, 0, &len) == KITTENX_ERROR);
+    CHECK(kittenx_voice_count(NULL) == 0);
+    kittenx_engine_free(NULL);
+
+    if (argc < 2) {
+        printf("kittenx %s: error paths ok, no model directory given\n", version);
+        return 0;
+    }
+
+    KittenxEngine *engine = kittenx_engine_new(argv[1], "cpu");
+    if (engine == NULL) {
+        fprintf(stderr, "failed to load model: %s\n", kittenx_last_error());
+        return 1;
+    }
+    CHECK(kittenx_sample_rate(engine) > 0);
+    CHECK(kittenx_voice_count(engine) > 0);
+    CHECK(kittenx_voice_name(engine, 0) != NULL);
+    CHECK(kittenx_voice_name(engine, kittenx_voice_count(engine)) == NULL);
+
+    /* Query the length, then fetch into a buffer of that size */
+    const char *text = "Hello from C.";
+    CHECK(kittenx_synthesize(engine, text, NULL, 1.0f, NULL, 0, &len) == KITTENX_BUFFER_TOO_SMALL);
+    CHECK(len > 0);
+    float *buffer = malloc(len * sizeof(float));
+    CHECK(buffer != NULL);
+    size_t written = 0;
+    CHECK(kittenx_synthesize(engine, text, NULL, 1.0f, buffer, len, &written) == KITTENX_OK);
+    CHECK(written == len);
+    free(buffer);
+
+    CHECK(kittenx_synthesize(engine, text, "no-such-voice", 1.0f, NULL, 0, &len) == KITTENX_ERROR);
+    CHECK(strstr(kittenx_last_error(), "no-such-voice") != NULL);
+
+    int chunks = 0;
+    CHECK(kittenx_synthesize_stream(engine, "First sentence. Second one!", kittenx_voice_name(engine, 0), 1.0f,
+                                    count_chunks, &chunks) == KITTENX_OK);
+    CHECK(chunks == 2);
+    CHECK(kittenx_synthesize_stream(engine, "First. Second.", NULL, 1.0f, stop_after_first, NULL) ==
+          KITTENX_CANCELLED);
+
+    kittenx_engine_free(engine);
+    printf("kittenx %s: ok\n", version);
+    return 0;
+}
@@ -0,0 +1,67 @@
+// Builds tests/c/smoke.c against include/kittenx.h and the cdylib, then runs it on the
+// synthetic model from tests/common (or the model in KITTENX_TEST_MODEL_DIR, if set), from
+// kittenx_engine_new through synthesis to kittenx_engine_free. CC overrides the C compiler
+// (default: cc).
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directories cargo may have put the cdylib in. A plain `cargo test` only builds it into
+/// target/<profile>/deps (this test's own directory); `cargo build` also copies it up to
+/// target/<profile>. The deps directory comes first so the freshly built library wins.
+fn library_dirs() -> Vec<PathBuf> {
+    let exe = std::env::current_exe().expect("test executable path");
+    let deps = exe.parent().expect("deps directory").to_path_buf();
+    let profile = deps.parent().expect("target directory").to_path_buf();
+    vec![deps, profile]
+}
+
+#[test]
+fn c_smoke_test() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dirs = library_dirs();
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let binary = out_dir.join("kittenx_c_smoke");
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let mut compile = Command::new(&cc);
+    compile
+        .arg(root.join("tests/c/smoke.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(root.join("include"));
+    for dir in &lib_dirs {
+        compile.arg("-L").arg(dir);
+        if !cfg!(windows) {
+            compile.arg(format!("-Wl,-rpath,{}", dir.display()));
+        }
+    }
+    compile.arg("-lkittenx").arg("-o").arg(&binary);
+    let status = match compile.status() {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("Skipping C smoke test: cannot run C compiler '{}': {}", cc, e);
+            return;
+        }
+    };
+    assert!(status.success(), "compiling tests/c/smoke.c failed");
+
+    let model_dir = match std::env::var_os("KITTENX_TEST_MODEL_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let dir = out_dir.join("c_api_model");
+            common::write_model_dir(&dir);
+            dir
+        }
+    };
+    let output = Command::new(&binary).arg(&model_dir).output().expect("run C smoke test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    print!("{}", stdout);
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "C smoke test failed");
+    assert!(stdout.contains(": ok"), "C smoke test did not synthesize");
+}
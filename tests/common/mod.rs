@@ -0,0 +1,145 @@
+// Shared by the integration tests: a small synthetic ONNX model with the KittenTTS inputs
+// and outputs, a model directory built around it, and the lookup of the bundled model
+#![allow(dead_code)]
+
+use kittenx::models::ModelConfig;
+use kittenx::onnx::proto::{write_bytes_field, write_varint_field};
+use kittenx::ModelFiles;
+use ndarray::Array2;
+use ndarray_npy::NpzWriter;
+use std::path::{Path, PathBuf};
+
+const FLOAT: u64 = 1;
+const INT64: u64 = 7;
+
+fn attribute_int(name: &str, value: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_bytes_field(&mut out, 1, name.as_bytes());
+    write_varint_field(&mut out, 3, value as u64);
+    write_varint_field(&mut out, 20, 2); // AttributeProto.INT
+    out
+}
+
+fn node(op_type: &str, inputs: &[&str], output: &str, attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for input in inputs {
+        write_bytes_field(&mut out, 1, input.as_bytes());
+    }
+    write_bytes_field(&mut out, 2, output.as_bytes());
+    write_bytes_field(&mut out, 3, output.as_bytes());
+    write_bytes_field(&mut out, 4, op_type.as_bytes());
+    for attribute in attributes {
+        write_bytes_field(&mut out, 5, attribute);
+    }
+    out
+}
+
+/// A tensor value with fixed (`Ok`) or symbolic (`Err`) dimensions; no dimensions leaves
+/// the shape undeclared
+fn value_info(name: &str, elem_type: u64, dims: &[Result<u64, &str>]) -> Vec<u8> {
+    let mut shape = Vec::new();
+    for dim in dims {
+        let mut d = Vec::new();
+        match dim {
+            Ok(value) => write_varint_field(&mut d, 1, *value),
+            Err(param) => write_bytes_field(&mut d, 2, param.as_bytes()),
+        }
+        write_bytes_field(&mut shape, 1, &d);
+    }
+    let mut tensor_type = Vec::new();
+    write_varint_field(&mut tensor_type, 1, elem_type);
+    if !dims.is_empty() {
+        write_bytes_field(&mut tensor_type, 2, &shape);
+    }
+    let mut type_proto = Vec::new();
+    write_bytes_field(&mut type_proto, 1, &tensor_type);
+
+    let mut out = Vec::new();
+    write_bytes_field(&mut out, 1, name.as_bytes());
+    write_bytes_field(&mut out, 2, &type_proto);
+    out
+}
+
+fn float_initializer(name: &str, dims: &[u64], values: &[f32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for &dim in dims {
+        write_varint_field(&mut out, 1, dim);
+    }
+    write_varint_field(&mut out, 2, FLOAT);
+    write_bytes_field(&mut out, 8, name.as_bytes());
+    let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    write_bytes_field(&mut out, 9, &raw);
+    out
+}
+
+/// tanh(concat(input_ids / speed * (style @ 1), style @ W)), plus all-ones token durations:
+/// exercises integer and float inputs, broadcasting and matmuls with the KittenTTS input
+/// names. The batch dimension is dynamic and batch items don't interact, so a batched run
+/// gives each item exactly what it gets on its own.
+pub fn synthetic_model() -> Vec<u8> {
+    let weights: Vec<f32> = (0..32).map(|i| ((i * 7 % 11) as f32 - 5.0) / 10.0).collect();
+    let mut graph = Vec::new();
+    for n in [
+        node("Cast", &["input_ids"], "ids_f", &[attribute_int("to", FLOAT as i64)]),
+        node("Div", &["ids_f", "speed"], "scaled", &[]),
+        node("MatMul", &["style", "ones"], "gain", &[]),
+        node("Mul", &["scaled", "gain"], "mixed", &[]),
+        node("MatMul", &["style", "weights"], "projected", &[]),
+        node("Concat", &["mixed", "projected"], "joined", &[attribute_int("axis", 1)]),
+        node("Tanh", &["joined"], "waveform", &[]),
+        node("Pow", &["ids_f", "zero"], "durations", &[]),
+    ] {
+        write_bytes_field(&mut graph, 1, &n);
+    }
+    write_bytes_field(&mut graph, 2, b"parity");
+    write_bytes_field(&mut graph, 5, &float_initializer("weights", &[4, 8], &weights));
+    write_bytes_field(&mut graph, 5, &float_initializer("ones", &[4, 1], &[1.0; 4]));
+    write_bytes_field(&mut graph, 5, &float_initializer("zero", &[], &[0.0]));
+    write_bytes_field(&mut graph, 11, &value_info("input_ids", INT64, &[Err("batch"), Err("tokens")]));
+    write_bytes_field(&mut graph, 11, &value_info("style", FLOAT, &[Err("batch"), Ok(4)]));
+    write_bytes_field(&mut graph, 11, &value_info("speed", FLOAT, &[Err("batch"), Ok(1)]));
+    write_bytes_field(&mut graph, 12, &value_info("waveform", FLOAT, &[]));
+    write_bytes_field(&mut graph, 12, &value_info("durations", FLOAT, &[Err("batch"), Err("tokens")]));
+
+    let mut opset = Vec::new();
+    write_varint_field(&mut opset, 2, 13);
+    let mut model = Vec::new();
+    write_varint_field(&mut model, 1, 8);
+    write_bytes_field(&mut model, 8, &opset);
+    write_bytes_field(&mut model, 7, &graph);
+    model
+}
+
+/// Voice written by `write_model_dir`
+pub const VOICE: &str = "expr-voice-5-m";
+
+/// Write the synthetic model, a one-voice `voices.npz` and a `config.json` to `dir`
+pub fn write_model_dir(dir: &Path) {
+    std::fs::create_dir_all(dir).expect("create model directory");
+    std::fs::write(dir.join("model.onnx"), synthetic_model()).expect("write model");
+
+    let styles = Array2::from_shape_fn((4, 4), |(row, col)| (row as f32 - col as f32) / 10.0);
+    let mut voices = NpzWriter::new(std::fs::File::create(dir.join("voices.npz")).expect("create voices"));
+    voices.add_array(VOICE, &styles).expect("write voice");
+    voices.finish().expect("finish voices");
+
+    let config = r#"{"name": "synthetic", "model_file": "model.onnx", "voices": "voices.npz", "sample_rate": 24000}"#;
+    std::fs::write(dir.join("config.json"), config).expect("write config");
+}
+
+/// Directory the bundled model is read from: `KITTENX_TEST_MODEL_DIR`, else models/
+pub fn bundled_model_dir() -> PathBuf {
+    std::env::var_os("KITTENX_TEST_MODEL_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("models"))
+}
+
+/// Files of the bundled model, if its config.json and .onnx file are present
+pub fn bundled_model_files() -> Option<ModelFiles> {
+    let dir = bundled_model_dir();
+    let config = ModelConfig::parse(&std::fs::read_to_string(dir.join("config.json")).ok()?).ok()?;
+    let model = dir.join(&config.model_file);
+    model
+        .exists()
+        .then(|| ModelFiles::new(model, dir.join(&config.voices)).with_config(dir.join("config.json")))
+}
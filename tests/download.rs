@@ -0,0 +1,307 @@
+// Exercises `Downloader` against a minimal local HTTP server: Range resume after an
+// interrupted transfer, retries on 5xx/429, SHA-256 and git blob id verification (fatal
+// when a pinned hash doesn't match), plus fetching a model into the shared hub cache from
+// a mirror set through HF_ENDPOINT.
+
+use kittenx::models::{ModelLocation, ModelSpec};
+use kittenx::utils::Downloader;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    /// Start offset of a `Range: bytes=<start>-` header
+    range: Option<u64>,
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    /// Content-Length to announce, if more than `body` (the connection is closed early)
+    content_length: Option<usize>,
+}
+
+impl Response {
+    fn new(status: u16, body: &[u8]) -> Self {
+        Self { status, headers: Vec::new(), body: body.to_vec(), content_length: None }
+    }
+}
+
+/// Serves each request with `handler(request, n)`, where `n` counts earlier requests with the
+/// same method. Returns the base URL and the log of requests.
+fn serve(handler: impl Fn(&Request, usize) -> Response + Send + 'static) -> (String, Arc<Mutex<Vec<Request>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let server_log = log.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let mut request = Request {
+                method: parts.next().unwrap_or_default().to_string(),
+                path: parts.next().unwrap_or_default().to_string(),
+                range: None,
+            };
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("range") {
+                        let start = value.trim().trim_start_matches("bytes=").trim_end_matches('-');
+                        request.range = start.parse().ok();
+                    }
+                }
+            }
+
+            let n = {
+                let mut log = server_log.lock().unwrap();
+                let n = log.iter().filter(|r: &&Request| r.method == request.method).count();
+                log.push(request.clone());
+                n
+            };
+            let response = handler(&request, n);
+            let length = response.content_length.unwrap_or(response.body.len());
+            let mut head = format!("HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, length);
+            for (name, value) in &response.headers {
+                head.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            head.push_str("\r\n");
+            let _ = stream.write_all(head.as_bytes());
+            if request.method != "HEAD" {
+                let _ = stream.write_all(&response.body);
+            }
+        }
+    });
+    (url, log)
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn body() -> Vec<u8> {
+    (0..100_000u32).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn fresh_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("download").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn destination(name: &str) -> PathBuf {
+    fresh_dir(name).join("model.onnx")
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    path.with_file_name("model.onnx.part")
+}
+
+fn downloader() -> Downloader {
+    Downloader::new().with_backoff(Duration::from_millis(10))
+}
+
+fn gets(log: &Mutex<Vec<Request>>) -> Vec<Request> {
+    log.lock().unwrap().iter().filter(|r| r.method == "GET").cloned().collect()
+}
+
+#[tokio::test]
+async fn resumes_interrupted_download_with_range() {
+    let body = body();
+    let served = body.clone();
+    let (url, log) = serve(move |request, n| match (n, request.range) {
+        // Announce the whole file but close the connection after 40 kB
+        (0, None) => Response { content_length: Some(served.len()), ..Response::new(200, &served[..40_000]) },
+        (_, Some(start)) => {
+            let start = start as usize;
+            let mut response = Response::new(206, &served[start..]);
+            response.headers.push(("Content-Range", format!("bytes {}-{}/{}", start, served.len() - 1, served.len())));
+            response
+        }
+        _ => Response::new(500, b"unexpected request"),
+    });
+    let path = destination("resume");
+
+    downloader()
+        .download(&format!("{}/model.onnx", url), &path, Some(&sha256(&body)))
+        .await
+        .expect("download");
+
+    assert_eq!(std::fs::read(&path).unwrap(), body);
+    assert!(!part_path(&path).exists(), "partial file left behind");
+    let ranges: Vec<Option<u64>> = gets(&log).iter().map(|r| r.range).collect();
+    assert_eq!(ranges, [None, Some(40_000)]);
+}
+
+#[tokio::test]
+async fn retries_server_errors_and_honours_retry_after() {
+    let body = body();
+    let served = body.clone();
+    let (url, log) = serve(move |request, n| match (request.method.as_str(), n) {
+        // No advertised hash
+        ("HEAD", _) => Response::new(200, b""),
+        (_, 0) => Response::new(503, b"unavailable"),
+        (_, 1) => {
+            let mut response = Response::new(429, b"slow down");
+            response.headers.push(("Retry-After", "1".to_string()));
+            response
+        }
+        _ => Response::new(200, &served),
+    });
+    let path = destination("retry");
+
+    let start = Instant::now();
+    downloader().download(&format!("{}/model.onnx", url), &path, None).await.expect("download");
+
+    assert_eq!(std::fs::read(&path).unwrap(), body);
+    assert_eq!(gets(&log).len(), 3);
+    assert!(start.elapsed() >= Duration::from_secs(1), "Retry-After was not honoured");
+}
+
+#[tokio::test]
+async fn does_not_retry_not_found() {
+    let (url, log) = serve(|_, _| Response::new(404, b"not found"));
+    let path = destination("not_found");
+
+    let error = downloader().download(&format!("{}/model.onnx", url), &path, None).await.unwrap_err();
+
+    assert!(format!("{:#}", error).contains("404"), "{:#}", error);
+    assert_eq!(gets(&log).len(), 1);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn retries_mismatch_with_advertised_hash() {
+    let served = body();
+    let (url, log) = serve(move |_, _| {
+        let mut response = Response::new(200, &served);
+        response.headers.push(("X-Linked-Etag", format!("\"{}\"", sha256(b"something else"))));
+        response
+    });
+    let path = destination("advertised_mismatch");
+
+    let error = downloader()
+        .with_retries(1)
+        .download(&format!("{}/model.onnx", url), &path, None)
+        .await
+        .unwrap_err();
+
+    assert!(format!("{:#}", error).contains("SHA-256 mismatch"), "{:#}", error);
+    assert_eq!(gets(&log).len(), 2, "a mismatch with the advertised hash should be retried once");
+    assert!(!path.exists(), "unverified file moved into place");
+    assert!(!part_path(&path).exists(), "unverified partial file kept");
+}
+
+#[tokio::test]
+async fn pinned_hash_mismatch_is_fatal() {
+    // The server advertises the hash of what it sends, but the caller pinned another one
+    let served = body();
+    let advertised = sha256(&served);
+    let (url, log) = serve(move |_, _| {
+        let mut response = Response::new(200, &served);
+        response.headers.push(("X-Linked-Etag", format!("\"{}\"", advertised)));
+        response
+    });
+    let path = destination("pinned_mismatch");
+    let pinned = sha256(b"something else");
+
+    let error = downloader()
+        .with_retries(3)
+        .with_required_checksum(true)
+        .download(&format!("{}/model.onnx", url), &path, Some(&pinned))
+        .await
+        .unwrap_err();
+
+    let message = format!("{:#}", error);
+    assert!(message.contains("SHA-256 mismatch") && message.contains(&pinned), "{}", message);
+    assert_eq!(gets(&log).len(), 1, "a mismatch with a pinned hash should not be retried");
+    assert!(!path.exists(), "unverified file moved into place");
+    assert!(!part_path(&path).exists(), "unverified partial file kept");
+}
+
+#[tokio::test]
+async fn refuses_unverified_file_when_a_checksum_is_required() {
+    let served = body();
+    let (url, log) = serve(move |_, _| Response::new(200, &served));
+    let path = destination("required");
+
+    let error = downloader()
+        .with_required_checksum(true)
+        .download(&format!("{}/model.onnx", url), &path, None)
+        .await
+        .unwrap_err();
+
+    assert!(format!("{:#}", error).contains("refusing to install"), "{:#}", error);
+    assert_eq!(gets(&log).len(), 1);
+    assert!(!path.exists(), "unverified file moved into place");
+    assert!(!part_path(&path).exists(), "unverified partial file kept");
+}
+
+#[tokio::test]
+async fn verifies_git_blob_id_etag() {
+    let config = br#"{"model_file": "model.onnx"}"#;
+    let mut blob = format!("blob {}\0", config.len()).into_bytes();
+    blob.extend_from_slice(config);
+    let blob_id = format!("{:x}", Sha1::digest(&blob));
+    let (url, _) = serve(move |_, _| {
+        let mut response = Response::new(200, config);
+        response.headers.push(("ETag", format!("\"{}\"", blob_id)));
+        response
+    });
+    let path = destination("git_blob");
+
+    downloader()
+        .with_required_checksum(true)
+        .download(&format!("{}/config.json", url), &path, None)
+        .await
+        .expect("download");
+
+    assert_eq!(std::fs::read(&path).unwrap(), config);
+}
+
+#[tokio::test]
+async fn fetches_into_the_hub_cache_layout() {
+    const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+    let model = body();
+    let model_sha256 = sha256(&model);
+    let (served, served_sha256) = (model.clone(), model_sha256.clone());
+    let (url, _) = serve(move |request, _| {
+        let (body, etag_header, etag) = match request.path.rsplit('/').next().unwrap_or_default() {
+            "config.json" => (br#"{"model_file": "model.onnx"}"#.to_vec(), "ETag", "\"c0ffee\"".to_string()),
+            // LFS files advertise their SHA-256
+            "model.onnx" => (served.clone(), "X-Linked-Etag", format!("\"{}\"", served_sha256)),
+            "voices.npz" => (b"voices".to_vec(), "ETag", "\"5ca1ab1e\"".to_string()),
+            _ => return Response::new(404, b"not found"),
+        };
+        let mut response = Response::new(200, &body);
+        response.headers.push(("X-Repo-Commit", COMMIT.to_string()));
+        response.headers.push((etag_header, etag));
+        response
+    });
+    std::env::set_var("HF_ENDPOINT", &url);
+    let cache_dir = fresh_dir("cache");
+    let spec = ModelSpec::resolve("owner/repo@v1").unwrap().offline(false);
+
+    let (files, _) = ModelLocation::Cache(cache_dir.clone()).fetch(&spec).await.expect("fetch");
+
+    let repo_dir = cache_dir.join("models--owner--repo");
+    assert_eq!(files.model, repo_dir.join("snapshots").join(COMMIT).join("model.onnx"));
+    assert_eq!(std::fs::read(&files.model).unwrap(), model);
+    assert!(repo_dir.join("blobs").join(&model_sha256).is_file());
+    assert_eq!(std::fs::read_to_string(repo_dir.join("refs/v1")).unwrap(), COMMIT);
+    assert!(ModelLocation::Cache(cache_dir).find(&spec).is_some(), "cached model not found again");
+}
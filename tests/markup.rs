@@ -0,0 +1,160 @@
+// Markdown and HTML cleaning: the speakable text and pauses produced for common constructs,
+// and the silence `synthesize` inserts between blocks.
+
+mod common;
+
+use kittenx::tts::markup::{
+    speakable_text, speech_blocks, CodeBlocks, InputFormat, SpeechBlock, HEADING_PAUSE, ITEM_PAUSE, PARAGRAPH_PAUSE,
+};
+
+fn texts(blocks: &[SpeechBlock]) -> Vec<&str> {
+    blocks.iter().map(|block| block.text.as_str()).collect()
+}
+
+fn markdown(input: &str) -> Vec<SpeechBlock> {
+    speech_blocks(input, InputFormat::Markdown, CodeBlocks::Announce)
+}
+
+fn html(input: &str) -> Vec<SpeechBlock> {
+    speech_blocks(input, InputFormat::Html, CodeBlocks::Announce)
+}
+
+#[test]
+fn plain_text_is_one_block() {
+    let blocks = speech_blocks("  # Not a heading\n[not](a link)  ", InputFormat::Text, CodeBlocks::Skip);
+    assert_eq!(texts(&blocks), ["# Not a heading\n[not](a link)"]);
+    assert!(speech_blocks(" \n ", InputFormat::Text, CodeBlocks::Announce).is_empty());
+}
+
+#[test]
+fn markdown_fenced_code() {
+    let input = "Before.\n\n```rust\nfn main() {}\n```\n\n~~~\nplain\n~~~\nAfter.";
+    assert_eq!(texts(&markdown(input)), ["Before.", "rust code block.", "Code block.", "After."]);
+
+    let skipped = speech_blocks(input, InputFormat::Markdown, CodeBlocks::Skip);
+    assert_eq!(texts(&skipped), ["Before.", "After."]);
+}
+
+#[test]
+fn markdown_indented_code() {
+    let input = "Run this:\n\n    cargo build --release\n    ./target/release/kittenx\n\nThen listen.";
+    assert_eq!(texts(&markdown(input)), ["Run this:", "Code block.", "Then listen."]);
+}
+
+#[test]
+fn markdown_headings() {
+    let input = "# Title\n\nIntro text.\n\nSetext heading\n==============\n\nBody.\n\nSecond level\n---\n## Closed ATX ##";
+    assert_eq!(
+        texts(&markdown(input)),
+        ["Title.", "Intro text.", "Setext heading.", "Body.", "Second level.", "Closed ATX."]
+    );
+}
+
+#[test]
+fn markdown_nested_lists() {
+    let input = "Steps:\n\n- Install it\n  - on Linux\n  - on macOS\n- Run **it**\n  with care\n1. First\n2) Second!\n- [x] Done task";
+    assert_eq!(
+        texts(&markdown(input)),
+        ["Steps:", "Install it.", "on Linux.", "on macOS.", "Run it with care.", "First.", "Second!", "Done task."]
+    );
+}
+
+#[test]
+fn markdown_tables() {
+    let input = "| Model | Size |\n|-------|:----:|\n| nano | 15M |\n| mini | 80M |";
+    assert_eq!(texts(&markdown(input)), ["Model, Size.", "nano, 15M.", "mini, 80M."]);
+}
+
+#[test]
+fn markdown_links_and_urls() {
+    let input = "Read the [docs](https://example.com/docs \"Docs\") and ![the logo](logo.png).\n\
+                 See <https://example.com> or https://example.com/a_b, [ref][1] and a note[^1].\n\
+                 Code `KittenTTS::new()` is *fine*, __bold__ and ~~gone~~ too (https://example.com).\n\n\
+                 [1]: https://example.com/ref";
+    assert_eq!(
+        texts(&markdown(input)),
+        ["Read the docs and the logo. See or, ref and a note. Code KittenTTS::new is fine, bold and gone too."]
+    );
+}
+
+#[test]
+fn markdown_blockquotes_and_rules() {
+    let input = "> Quoted\n> text.\n\n***\n\nAfter the rule.";
+    assert_eq!(texts(&markdown(input)), ["Quoted text.", "After the rule."]);
+}
+
+#[test]
+fn markdown_pauses() {
+    let blocks = markdown("Intro.\n\n# Heading\n\n- one\n- two\n\nOutro.");
+    let pauses: Vec<f32> = blocks.iter().map(|block| block.pause_secs).collect();
+    // A heading lengthens the pause before it as well as after it
+    assert_eq!(pauses, [HEADING_PAUSE, HEADING_PAUSE, ITEM_PAUSE, ITEM_PAUSE, PARAGRAPH_PAUSE]);
+}
+
+#[test]
+fn html_pre_script_and_entities() {
+    let input = "<html><head><title>Page</title><style>p { color: red; }</style>\
+                 <script>alert(\"<p>hi</p>\")</script></head><body>\
+                 <h1>Fish &amp; Chips</h1><!-- a comment -->\
+                 <p>It&rsquo;s &lt;great&gt; &#8212; really&#x21;</p>\
+                 <pre><code>let x = 1;</code></pre>\
+                 <p>See <a href=\"https://example.com\">the menu</a> <img src=\"a.png\" alt=\"a fish\">.</p>\
+                 </body></html>";
+    assert_eq!(
+        texts(&html(input)),
+        ["Page", "Fish & Chips.", "It's <great> — really!", "Code block.", "See the menu a fish."]
+    );
+    let skipped = speech_blocks(input, InputFormat::Html, CodeBlocks::Skip);
+    assert!(!texts(&skipped).contains(&"Code block."));
+}
+
+#[test]
+fn html_lists_and_tables() {
+    let input = "<ul><li>One</li><li>Two<br>lines</li></ul>\
+                 <table><tr><th>Model</th><th>Size</th></tr><tr><td>nano</td><td>15M</td></tr></table>";
+    let blocks = html(input);
+    assert_eq!(texts(&blocks), ["One.", "Two.", "lines.", "Model, Size.", "nano, 15M."]);
+    assert!(blocks.iter().all(|block| block.pause_secs == ITEM_PAUSE), "{:?}", blocks);
+}
+
+#[test]
+fn speakable_text_joins_blocks_by_line() {
+    let text = speakable_text("# Title\nSome *text*.", InputFormat::Markdown, CodeBlocks::Announce);
+    assert_eq!(text, "Title.\nSome text.");
+}
+
+#[cfg(all(feature = "g2p", any(feature = "onnxruntime", feature = "tract")))]
+#[test]
+fn synthesis_inserts_pauses_between_blocks() {
+    use kittenx::tts::phonemizer::PhonemizerBackend;
+    use kittenx::{KittenTTS, ModelFiles, PostProcessing, SynthesisRequest};
+    use std::path::PathBuf;
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("markup_model");
+    common::write_model_dir(&dir);
+    let tts = KittenTTS::builder()
+        .model_files(ModelFiles::new(dir.join("model.onnx"), dir.join("voices.npz")).with_config(dir.join("config.json")))
+        .phonemizer(PhonemizerBackend::G2p.build().unwrap())
+        .post_processing(PostProcessing { trim: None, normalize_peak: None })
+        .build()
+        .expect("load synthetic model");
+
+    let input = "# Title\n\nFirst paragraph.\n\n- An item";
+    let blocks = markdown(input);
+    let spoken: usize = blocks
+        .iter()
+        .map(|block| {
+            let request = SynthesisRequest::new(block.text.as_str()).voice(common::VOICE);
+            tts.synthesize(&request).unwrap().samples.len()
+        })
+        .sum();
+    // Silence follows every block but the last
+    let silence: usize = blocks[..blocks.len() - 1]
+        .iter()
+        .map(|block| (block.pause_secs * tts.sample_rate() as f32) as usize)
+        .sum();
+
+    let request = SynthesisRequest::new(input).voice(common::VOICE).input_format(InputFormat::Markdown);
+    let audio = tts.synthesize(&request).unwrap();
+    assert_eq!(audio.samples.len(), spoken + silence);
+}
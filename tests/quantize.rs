@@ -0,0 +1,55 @@
+// Quantizes the synthetic model from tests/common and checks that the result still loads,
+// keeps the model's inputs and outputs, and sounds close to the original.
+
+#![cfg(any(feature = "onnxruntime", feature = "tract"))]
+
+mod common;
+
+use kittenx::onnx::{quantize_file, Backend, InferenceBackend, QuantizeOptions};
+use kittenx::{AccelerationProvider, SessionOptions};
+use std::path::Path;
+use std::sync::Arc;
+
+fn load(path: &Path) -> Arc<dyn InferenceBackend> {
+    Backend::default()
+        .load(path, AccelerationProvider::Cpu, &SessionOptions::default())
+        .unwrap_or_else(|e| panic!("failed to load {}: {:#}", path.display(), e))
+}
+
+fn check_quantized(name: &str, integer_matmul: bool) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("quantize").join(name);
+    std::fs::create_dir_all(&dir).expect("create directory");
+    let (original_path, quantized_path) = (dir.join("model.onnx"), dir.join("model-int8.onnx"));
+    std::fs::write(&original_path, common::synthetic_model()).expect("write synthetic model");
+
+    // The synthetic weights are tiny, so lower the size threshold to quantize them
+    let options = QuantizeOptions { min_elements: 16, integer_matmul };
+    let report = quantize_file(&original_path, &quantized_path, &options).expect("quantize");
+    assert_eq!(report.quantized_parameters, 32, "{:?}", report);
+    assert_eq!(report.integer_matmuls, usize::from(integer_matmul), "{:?}", report);
+
+    let (original, quantized) = (load(&original_path), load(&quantized_path));
+    assert_eq!(original.signature(), quantized.signature());
+
+    let cases: [(Vec<i64>, [f32; 4], f32); 2] = [
+        (vec![0, 12, 47, 3, 0], [0.1, -0.2, 0.3, 0.05], 1.0),
+        ((0..64).collect(), [-0.01, 0.02, -0.03, 0.04], 1.5),
+    ];
+    for (tokens, style, speed) in cases {
+        let expected = original.infer(vec![tokens.clone()], style.to_vec(), speed).expect("original inference");
+        let actual = quantized.infer(vec![tokens.clone()], style.to_vec(), speed).expect("quantized inference");
+        assert_eq!(expected.shape(), actual.shape(), "output shapes differ for {:?}", tokens);
+        let max_diff = expected.iter().zip(actual.iter()).map(|(a, b)| (a - b).abs()).fold(0.0f32, f32::max);
+        assert!(max_diff < 0.01, "outputs differ by {} for {:?}", max_diff, tokens);
+    }
+}
+
+#[test]
+fn quantized_model_matches_original() {
+    check_quantized("integer_matmul", true);
+}
+
+#[test]
+fn dequantized_weights_match_original() {
+    check_quantized("dequantize", false);
+}
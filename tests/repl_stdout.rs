@@ -0,0 +1,66 @@
+// Checks that `kittenx repl --stdout` writes nothing but audio to stdout: model loading,
+// provider selection and colon-command output all have to go to stderr.
+
+mod common;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Split `bytes` into consecutive RIFF files, failing on anything that isn't one
+fn split_wavs(mut bytes: &[u8]) -> Vec<&[u8]> {
+    let mut wavs = Vec::new();
+    while !bytes.is_empty() {
+        assert!(
+            bytes.len() >= 8 && &bytes[..4] == b"RIFF",
+            "stdout has non-audio bytes: {:?}",
+            String::from_utf8_lossy(&bytes[..bytes.len().min(80)])
+        );
+        let len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize + 8;
+        assert!(len <= bytes.len(), "truncated WAV on stdout");
+        wavs.push(&bytes[..len]);
+        bytes = &bytes[len..];
+    }
+    wavs
+}
+
+#[test]
+fn repl_stdout_is_audio_only() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("repl_stdout");
+    let model_dir = root.join("model");
+    common::write_model_dir(&model_dir);
+    // Keep config files from the developer's machine out of the run
+    let config_home = root.join("config");
+    std::fs::create_dir_all(&config_home).expect("create config dir");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kittenx"))
+        .current_dir(&root)
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env_remove("KITTENX_CONFIG")
+        .args(["--offline", "repl", "--stdout", "--format", "wav", "--no-trim", "--phonemizer", "g2p"])
+        .args(["--voice", common::VOICE])
+        .arg("--model-dir")
+        .arg(&model_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("start kittenx");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"Hello world.\n:voice\n:show\n:phonemes on\nHello again.\n:bogus\n")
+        .expect("write stdin");
+    let output = child.wait_with_output().expect("run kittenx");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "kittenx repl failed: {}", stderr);
+
+    let wavs = split_wavs(&output.stdout);
+    assert_eq!(wavs.len(), 2, "expected one WAV per text line; stderr: {}", stderr);
+    for wav in wavs {
+        let reader = hound::WavReader::new(wav).expect("valid WAV");
+        assert!(reader.len() > 0, "empty WAV");
+    }
+    assert!(stderr.contains("Voices:") && stderr.contains("Phonemes:"), "stderr: {}", stderr);
+}
@@ -0,0 +1,50 @@
+// Smoke test for the wasm bindings under node:
+//   wasm-pack build --target nodejs --out-dir target/wasm-node -- --no-default-features --features wasm
+//   node tests/wasm/smoke.mjs <model_dir>
+// The model directory needs the .onnx file named in its config.json and the voices NPZ.
+
+import { readFileSync } from "node:fs";
+import { join } from "node:path";
+import { createRequire } from "node:module";
+
+const require = createRequire(import.meta.url);
+const { KittenTTS } = require("../../target/wasm-node/kittenx.js");
+
+const modelDir = process.argv[2];
+if (!modelDir) {
+  console.error("usage: node tests/wasm/smoke.mjs <model_dir>");
+  process.exit(2);
+}
+
+const configText = readFileSync(join(modelDir, "config.json"), "utf8");
+const config = JSON.parse(configText);
+const tts = new KittenTTS(
+  readFileSync(join(modelDir, config.model_file)),
+  readFileSync(join(modelDir, config.voices ?? "voices.npz")),
+  configText,
+);
+
+const check = (cond, message) => {
+  if (!cond) {
+    console.error(`check failed: ${message}`);
+    process.exit(1);
+  }
+};
+
+check(tts.sampleRate > 0, "sample rate");
+check(tts.voices.length > 0, "voices");
+
+const audio = tts.generate("Hello from WebAssembly.", tts.voices[0], 1.0);
+check(audio instanceof Float32Array, "generate returns a Float32Array");
+check(audio.length > tts.sampleRate / 4, "at least a quarter second of audio");
+check(audio.every(Number.isFinite), "finite samples");
+
+let threw = false;
+try {
+  tts.generate("Hello", "no-such-voice", 1.0);
+} catch (e) {
+  threw = String(e).includes("no-such-voice");
+}
+check(threw, "unknown voice raises an error naming it");
+
+console.log(`wasm: ok, ${audio.length} samples at ${tts.sampleRate} Hz`);